@@ -0,0 +1,87 @@
+//! Values that can be read and updated through a shared reference from any thread, which is how a
+//! Scalar's data, gradient and label are stored.
+
+use std::cmp::Ordering;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+use std::sync::atomic::{self, AtomicU64};
+use std::sync::Mutex;
+
+use crate::Float;
+
+// A Scalar's data and gradient are never used to synchronize anything else, so every access can
+// be relaxed.
+const ORDER: atomic::Ordering = atomic::Ordering::Relaxed;
+
+pub(crate) struct AtomicFloat<F: Float> {
+    bits: AtomicU64,
+    _float: PhantomData<F>,
+}
+
+impl<F: Float> AtomicFloat<F> {
+    pub(crate) fn new(value: F) -> Self {
+        AtomicFloat {
+            bits: AtomicU64::new(value.to_bits64()),
+            _float: PhantomData,
+        }
+    }
+
+    pub(crate) fn get(&self) -> F {
+        F::from_bits64(self.bits.load(ORDER))
+    }
+
+    pub(crate) fn set(&self, value: F) {
+        self.bits.store(value.to_bits64(), ORDER);
+    }
+
+    /// Adds `delta` onto the stored value in a single step, so that two threads adding onto the
+    /// same float at once cannot lose either addition.
+    pub(crate) fn add(&self, delta: F) {
+        let _ = self.bits.fetch_update(ORDER, ORDER, |bits| {
+            Some((F::from_bits64(bits) + delta).to_bits64())
+        });
+    }
+}
+
+impl<F: Float> PartialEq for AtomicFloat<F> {
+    fn eq(&self, other: &Self) -> bool {
+        self.get() == other.get()
+    }
+}
+
+impl<F: Float> PartialOrd for AtomicFloat<F> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.get().partial_cmp(&other.get())
+    }
+}
+
+impl<F: Float> Debug for AtomicFloat<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.get())
+    }
+}
+
+/// A label that can be replaced through a shared reference. A `&'static str` is too wide to be
+/// swapped atomically, so it is kept behind a lock instead.
+pub(crate) struct SharedLabel(Mutex<&'static str>);
+
+impl SharedLabel {
+    pub(crate) fn new(label: &'static str) -> Self {
+        SharedLabel(Mutex::new(label))
+    }
+
+    pub(crate) fn get(&self) -> &'static str {
+        // The lock is never held across anything that could panic, so it cannot be poisoned.
+        *self
+            .0
+            .lock()
+            .expect("A label's lock should never be poisoned!")
+    }
+
+    pub(crate) fn set(&self, label: &'static str) {
+        *self
+            .0
+            .lock()
+            .expect("A label's lock should never be poisoned!") = label;
+    }
+}
@@ -0,0 +1,80 @@
+//! Numerical verification of the gradients computed by `backward`, and assertions for testing
+//! them.
+
+use crate::{Derivable, Float, GenericScalar, Operation};
+
+/// Compares the gradient that `backward` computes for `f` at `at` against a central finite
+/// difference, `(f(at + eps) - f(at - eps)) / 2eps`, returning both as `(analytic, numeric)`.
+/// `f` builds an expression out of the leaf Scalar it is given. The two should agree closely for
+/// any correctly implemented operation, which makes this useful for testing new ones; a smaller
+/// `eps` reduces the error of the estimate, until rounding error takes over.
+///
+/// ```
+/// use minigrad::{grad_check, Scalar};
+///
+/// let (analytic, numeric) = grad_check(|x: &Scalar| x.tanh(), 0.5, 1e-3);
+/// assert!((analytic - numeric).abs() < 1e-3);
+/// ```
+pub fn grad_check<F: Float, G>(f: G, at: F, eps: F) -> (F, F)
+where
+    G: Fn(&GenericScalar<F>) -> GenericScalar<F>,
+{
+    let leaf = |data| GenericScalar::new_op(data, vec![], Operation::Base, "x");
+
+    let x = leaf(at);
+    f(&x).backward();
+    let analytic = x.join_grad();
+
+    let above = f(&leaf(at + eps)).join_data();
+    let below = f(&leaf(at - eps)).join_data();
+    let numeric = (above - below) / (F::cast(2) * eps);
+
+    (analytic, numeric)
+}
+
+/// Asserts that the gradient of `node` is at most `ulps` representable values away from
+/// `expected`, panicking with both values if it is not. This is the same tolerance the crate's own
+/// tests use (with `ulps = 4`), which absorbs the rounding error of a few operations.
+///
+/// ```
+/// use minigrad::{assert_grad_close, Derivable, Scalar};
+///
+/// let a = Scalar::new(0.1, "a");
+/// let b = &(&a * 3.0) + 0.2;
+/// b.backward();
+/// assert_grad_close(&a, 3.0, 4);
+/// ```
+#[track_caller]
+pub fn assert_grad_close<F: Float>(node: &GenericScalar<F>, expected: F, ulps: u32) {
+    let grad = node.join_grad();
+    assert!(
+        grad.ulps_eq(expected, ulps),
+        "The gradient of `{}` is {}, which is more than {} ulps from {}",
+        node.label(),
+        grad,
+        ulps,
+        expected
+    );
+}
+
+/// Like [`assert_grad_close`], but compares the data of `node` rather than its gradient.
+///
+/// ```
+/// use minigrad::{assert_data_close, Scalar};
+///
+/// let a = Scalar::new(0.1, "a");
+/// let b = &a + 0.2;
+/// assert_data_close(&b, 0.3, 4);
+/// ```
+#[track_caller]
+pub fn assert_data_close<F: Float>(node: &GenericScalar<F>, expected: F, ulps: u32) {
+    let data = node.join_data();
+    assert!(
+        data.ulps_eq(expected, ulps),
+        "The data of `{}` is {}, which is more than {} ulps from {}",
+        node.label(),
+        data,
+        ulps,
+        expected
+    );
+}
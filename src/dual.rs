@@ -0,0 +1,182 @@
+//! Forward-mode differentiation with dual numbers, as an alternative to building a graph and
+//! calling `backward`.
+
+use std::fmt::Display;
+use std::ops;
+
+use crate::{Float, Numeric};
+
+/// A dual number, carrying a value together with its derivative along some direction. Every
+/// operation on dual numbers computes both at once, so a single pass through a function gives its
+/// value and its (directional) derivative, without building a graph. This is cheaper than reverse
+/// mode (`backward`) when a function has few inputs and many outputs. Most code should use the
+/// [`Dual`] alias.
+///
+/// ```
+/// use minigrad::Dual;
+///
+/// // Seeding x with a derivative of 1 differentiates with respect to it.
+/// let x = Dual::variable(3.0);
+/// let y = x * x + Dual::constant(1.0);
+/// assert_eq!(y.value(), 10.0);
+/// assert_eq!(y.derivative(), 6.0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct GenericDual<F: Float> {
+    value: F,
+    derivative: F,
+}
+
+/// A dual number storing `f32`s.
+pub type Dual = GenericDual<f32>;
+
+/// A dual number storing `f64`s.
+pub type Dual64 = GenericDual<f64>;
+
+impl<F: Float> GenericDual<F> {
+    /// Creates a dual number holding `value`, with a derivative of `derivative`.
+    pub fn new(value: impl Numeric, derivative: impl Numeric) -> Self {
+        GenericDual {
+            value: value.to_float(),
+            derivative: derivative.to_float(),
+        }
+    }
+
+    /// Creates the dual number for the input being differentiated with respect to, whose
+    /// derivative (with respect to itself) is 1.
+    pub fn variable(value: impl Numeric) -> Self {
+        GenericDual::new(value, 1)
+    }
+
+    /// Creates a dual number for a constant, whose derivative is 0.
+    pub fn constant(value: impl Numeric) -> Self {
+        GenericDual::new(value, 0)
+    }
+
+    /// Returns the value of this dual number.
+    pub fn value(&self) -> F {
+        self.value
+    }
+
+    /// Returns the derivative carried by this dual number.
+    pub fn derivative(&self) -> F {
+        self.derivative
+    }
+
+    // Applies a function with value `value` and derivative `slope` at this dual number's value,
+    // following the chain rule.
+    fn chain(self, value: F, slope: F) -> Self {
+        GenericDual {
+            value,
+            derivative: slope * self.derivative,
+        }
+    }
+
+    /// Computes e^x.
+    pub fn exp(self) -> Self {
+        let value = self.value.exp();
+        self.chain(value, value)
+    }
+
+    /// Takes the natural logarithm.
+    pub fn ln(self) -> Self {
+        self.chain(self.value.ln(), self.value.recip())
+    }
+
+    /// Takes the square root.
+    pub fn sqrt(self) -> Self {
+        let value = self.value.sqrt();
+        self.chain(value, (F::cast(2) * value).recip())
+    }
+
+    /// Applies the hyperbolic tangent.
+    pub fn tanh(self) -> Self {
+        let value = self.value.tanh();
+        self.chain(value, F::one() - value * value)
+    }
+
+    /// Takes the sine, in radians.
+    pub fn sin(self) -> Self {
+        self.chain(self.value.sin(), self.value.cos())
+    }
+
+    /// Takes the cosine, in radians.
+    pub fn cos(self) -> Self {
+        self.chain(self.value.cos(), -self.value.sin())
+    }
+
+    /// Raises this dual number to a constant power, `exponent`.
+    pub fn pow(self, exponent: F) -> Self {
+        let slope = exponent * self.value.powf(exponent - F::one());
+        self.chain(self.value.powf(exponent), slope)
+    }
+
+    /// Raises this dual number to a constant integer power, `n`.
+    pub fn powi(self, n: i32) -> Self {
+        let slope = if n == 0 {
+            F::zero()
+        } else {
+            F::cast(n) * self.value.powi(n - 1)
+        };
+        self.chain(self.value.powi(n), slope)
+    }
+}
+
+impl<F: Float> Display for GenericDual<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} + {}ε", self.value, self.derivative)
+    }
+}
+
+impl<F: Float> ops::Add for GenericDual<F> {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self::Output {
+        GenericDual {
+            value: self.value + rhs.value,
+            derivative: self.derivative + rhs.derivative,
+        }
+    }
+}
+
+impl<F: Float> ops::Sub for GenericDual<F> {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self::Output {
+        GenericDual {
+            value: self.value - rhs.value,
+            derivative: self.derivative - rhs.derivative,
+        }
+    }
+}
+
+impl<F: Float> ops::Mul for GenericDual<F> {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self::Output {
+        // The product rule: (ab)' = a'b + ab'.
+        GenericDual {
+            value: self.value * rhs.value,
+            derivative: self.derivative * rhs.value + self.value * rhs.derivative,
+        }
+    }
+}
+
+impl<F: Float> ops::Div for GenericDual<F> {
+    type Output = Self;
+    fn div(self, rhs: Self) -> Self::Output {
+        // The quotient rule: (a / b)' = (a'b - ab') / b².
+        GenericDual {
+            value: self.value / rhs.value,
+            derivative: (self.derivative * rhs.value - self.value * rhs.derivative)
+                / (rhs.value * rhs.value),
+        }
+    }
+}
+
+impl<F: Float> ops::Neg for GenericDual<F> {
+    type Output = Self;
+    fn neg(self) -> Self::Output {
+        GenericDual {
+            value: -self.value,
+            derivative: -self.derivative,
+        }
+    }
+}
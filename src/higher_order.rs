@@ -0,0 +1,191 @@
+//! Gradients that are themselves Scalars, for computing higher-order derivatives.
+//!
+//! `backward` writes plain numbers into each Scalar's gradient, which cannot be differentiated any
+//! further. `grad_graph` instead builds every gradient out of Scalar operations, so each gradient
+//! is a Scalar with its own graph, and calling `backward` on it gives second-order derivatives.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::{parse_topology, Float, GenericScalar, Operation};
+
+// Nodes are identified by address, since distinct Scalars may hold equal data.
+fn key<F: Float>(node: &GenericScalar<F>) -> *const () {
+    Arc::as_ptr(&node.inner) as *const ()
+}
+
+// Literals in the gradient rules (such as the `2` in ∂(a²)/∂a = 2a) are constants, so that they
+// are not mistaken for parameters of the gradient's graph.
+fn constant<F: Float>(data: F) -> GenericScalar<F> {
+    GenericScalar::constant(data)
+}
+
+// Adds `contribution` to the gradient accumulated so far for `node`.
+fn accumulate<F: Float>(
+    grads: &mut HashMap<*const (), GenericScalar<F>>,
+    node: &GenericScalar<F>,
+    contribution: GenericScalar<F>,
+) {
+    let total = match grads.get(&key(node)) {
+        Some(grad) => grad + &contribution,
+        None => contribution,
+    };
+    grads.insert(key(node), total);
+}
+
+impl<F: Float> GenericScalar<F> {
+    /// Computes the gradient of this Scalar with respect to each of `wrt`, in the same order, as
+    /// Scalars whose graphs record how the gradients were computed. Calling `backward` on one of
+    /// them then differentiates the gradient itself. Unlike `backward`, this does not touch the
+    /// numeric gradient of any Scalar. A Scalar in `wrt` that this Scalar does not depend on gets
+    /// a gradient of zero.
+    ///
+    /// ```
+    /// use minigrad::{Derivable, Scalar};
+    ///
+    /// let x = Scalar::new(2.0, "x");
+    /// let y = x.pow(3.0);
+    ///
+    /// // ∂y/∂x = 3x² = 12
+    /// let dy_dx = y.grad_graph(&[&x]).remove(0);
+    /// assert_eq!(dy_dx.data(), 12.0);
+    ///
+    /// // ∂²y/∂x² = 6x = 12
+    /// dy_dx.backward();
+    /// assert_eq!(x.grad(), 12.0);
+    /// ```
+    pub fn grad_graph(&self, wrt: &[&Self]) -> Vec<GenericScalar<F>> {
+        let mut grads = HashMap::new();
+        grads.insert(key(self), constant(F::one()));
+
+        for node in parse_topology(self, false) {
+            let grad = match grads.get(&key(node)) {
+                Some(grad) => grad.clone(),
+                None => continue,
+            };
+            let children = &node.inner._children;
+
+            // The same rules as in `derive`, but built out of Scalar operations.
+            match node.inner._op {
+                Operation::Add => {
+                    accumulate(&mut grads, &children[0], grad.clone());
+                    accumulate(&mut grads, &children[1], grad);
+                }
+                Operation::Sub => {
+                    accumulate(&mut grads, &children[0], grad.clone());
+                    accumulate(&mut grads, &children[1], -&grad);
+                }
+                Operation::Mul => {
+                    accumulate(&mut grads, &children[0], &grad * &children[1]);
+                    accumulate(&mut grads, &children[1], &grad * &children[0]);
+                }
+                Operation::Div => {
+                    let (a, b) = (&children[0], &children[1]);
+                    accumulate(&mut grads, a, &grad / b);
+                    accumulate(&mut grads, b, -&(&(&grad * a) / &b.pow(F::cast(2))));
+                }
+                Operation::Pow => {
+                    let exponent = node.pow_exponent();
+                    let a = &children[0];
+                    let scale = &grad * &constant(exponent);
+                    accumulate(&mut grads, a, &scale * &a.pow(exponent - F::one()));
+                }
+                Operation::Powi => {
+                    let n = node.powi_exponent();
+                    if n != 0 {
+                        let a = &children[0];
+                        let scale = &grad * &constant(F::cast(n));
+                        accumulate(&mut grads, a, &scale * &a.powi(n - 1));
+                    }
+                }
+                Operation::Exp => accumulate(&mut grads, &children[0], &grad * node),
+                Operation::Tanh => {
+                    let slope = &constant(F::one()) - &node.pow(F::cast(2));
+                    accumulate(&mut grads, &children[0], &grad * &slope);
+                }
+                Operation::Sigmoid => {
+                    let slope = node * &(&constant(F::one()) - node);
+                    accumulate(&mut grads, &children[0], &grad * &slope);
+                }
+                Operation::Sqrt => {
+                    let slope = &constant(F::cast(2)) * node;
+                    accumulate(&mut grads, &children[0], &grad / &slope);
+                }
+                Operation::Recip => {
+                    accumulate(&mut grads, &children[0], -&(&grad * &(node * node)));
+                }
+                Operation::Sin => {
+                    accumulate(&mut grads, &children[0], &grad * &children[0].cos());
+                }
+                Operation::Cos => {
+                    accumulate(&mut grads, &children[0], -&(&grad * &children[0].sin()));
+                }
+                Operation::Sinh => {
+                    accumulate(&mut grads, &children[0], &grad * &children[0].cosh());
+                }
+                Operation::Cosh => {
+                    accumulate(&mut grads, &children[0], &grad * &children[0].sinh());
+                }
+                Operation::Ln => {
+                    accumulate(&mut grads, &children[0], &grad / &children[0]);
+                }
+                Operation::Neg => accumulate(&mut grads, &children[0], -&grad),
+                // The piecewise operations only ever scale the gradient by a constant.
+                Operation::ReLU | Operation::Abs => {
+                    let a = children[0].join_data();
+                    let slope = if node.inner._op == Operation::ReLU {
+                        if a > F::zero() {
+                            F::one()
+                        } else {
+                            F::zero()
+                        }
+                    } else if a == F::zero() {
+                        F::zero()
+                    } else {
+                        a.signum()
+                    };
+                    accumulate(&mut grads, &children[0], &grad * &constant(slope));
+                }
+                Operation::Max | Operation::Min => {
+                    let (a, b) = (children[0].join_data(), children[1].join_data());
+                    let first_selected = match node.inner._op {
+                        Operation::Max => a >= b,
+                        _ => a <= b,
+                    };
+                    let selected = &children[if first_selected { 0 } else { 1 }];
+                    accumulate(&mut grads, selected, grad);
+                }
+                Operation::Sum => {
+                    for child in children.iter() {
+                        accumulate(&mut grads, child, grad.clone());
+                    }
+                }
+                Operation::Mean => {
+                    let count = constant(F::cast(children.len()));
+                    for child in children.iter() {
+                        accumulate(&mut grads, child, &grad / &count);
+                    }
+                }
+                Operation::PowVar => {
+                    let (a, b) = (&children[0], &children[1]);
+                    let exponent = b - &constant(F::one());
+                    accumulate(&mut grads, a, &(&grad * b) * &a.powf(&exponent));
+                    // As in `derive`, ln(a) is undefined for a ≤ 0, so `b` then gets no gradient.
+                    if a.join_data() > F::zero() {
+                        accumulate(&mut grads, b, &(&grad * node) * &a.ln());
+                    }
+                }
+                Operation::Base => (),
+            }
+        }
+
+        wrt.iter()
+            .map(|node| {
+                grads
+                    .get(&key(node))
+                    .cloned()
+                    .unwrap_or_else(|| constant(F::zero()))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,53 @@
+//! Random initialization of leaf Scalars, such as the weights of a network, behind the `rand`
+//! feature.
+
+use std::f64::consts::TAU;
+
+use rand::Rng;
+
+use crate::{Float, GenericScalar, Operation};
+
+impl<F: Float> GenericScalar<F> {
+    /// Creates a leaf Scalar holding a value drawn from the normal distribution with the given
+    /// `mean` and standard deviation `std`, using the thread-local random number generator. Use
+    /// `randn_with` for a reproducible value.
+    pub fn randn(mean: F, std: F, label: &'static str) -> Self {
+        GenericScalar::randn_with(&mut rand::thread_rng(), mean, std, label)
+    }
+
+    /// Like `randn`, but draws the value from `rng`, so that seeding `rng` (for instance, with
+    /// `StdRng::seed_from_u64`) gives the same values on every run.
+    pub fn randn_with<R: Rng + ?Sized>(rng: &mut R, mean: F, std: F, label: &'static str) -> Self {
+        // The Box-Muller transform, which turns two uniform samples into a normal one. The first
+        // sample is taken from (0, 1] rather than [0, 1), so that its logarithm is finite.
+        let u1 = 1.0 - rng.gen::<f64>();
+        let u2 = rng.gen::<f64>();
+        let z = (-2.0 * u1.ln()).sqrt() * (TAU * u2).cos();
+
+        let data = mean + std * F::cast(z);
+        GenericScalar::new_op(data, vec![], Operation::Base, label)
+    }
+
+    /// Creates a leaf Scalar holding a value drawn uniformly from `[lo, hi)`, using the
+    /// thread-local random number generator. Use `uniform_with` for a reproducible value. Panics
+    /// if `lo` is not less than `hi`.
+    pub fn uniform(lo: F, hi: F, label: &'static str) -> Self {
+        GenericScalar::uniform_with(&mut rand::thread_rng(), lo, hi, label)
+    }
+
+    /// Like `uniform`, but draws the value from `rng`, so that seeding `rng` gives the same values
+    /// on every run.
+    pub fn uniform_with<R: Rng + ?Sized>(rng: &mut R, lo: F, hi: F, label: &'static str) -> Self {
+        assert!(lo < hi, "Cannot sample uniformly from [{}, {})!", lo, hi);
+
+        // Values just below `hi` may round up to `hi` itself once cast to `F` and scaled
+        // (especially for an `f32`), so those are drawn again to keep the range half-open.
+        loop {
+            let u: F = F::cast(rng.gen::<f64>());
+            let data = lo + (hi - lo) * u;
+            if data < hi {
+                return GenericScalar::new_op(data, vec![], Operation::Base, label);
+            }
+        }
+    }
+}
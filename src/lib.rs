@@ -4,33 +4,104 @@
 #![warn(missing_debug_implementations, missing_docs, rust_2018_idioms)]
 #![allow(dead_code)]
 
+mod atomic;
+mod check;
+mod dual;
+mod higher_order;
+#[cfg(feature = "rand")]
+mod init;
+pub mod nn;
 mod numeric;
+pub mod optim;
+mod reduce;
+#[cfg(feature = "serde")]
+mod serialize;
+mod tape;
+
+use crate::atomic::{AtomicFloat, SharedLabel};
+pub use crate::check::{assert_data_close, assert_grad_close, grad_check};
+pub use crate::dual::{Dual, Dual64, GenericDual};
+pub use crate::numeric::{Float, Numeric, NumericError};
+pub use crate::reduce::{dot, fold, mean, product, reduce, sum, FoldError};
+pub use crate::tape::{GenericTape, Tape, Tape64};
 
 use derivative::{self, Derivative}; // Allows for ignoring a label field when comparing Scalars
-use std::cell::{Cell, RefCell}; // Allows for interior mutability of a Scalar's gradient
+use std::collections::{HashMap, HashSet};
 use std::fmt::{Debug, Display};
+use std::num::ParseFloatError;
 use std::ops;
+use std::str::FromStr;
+use std::sync::Arc;
 
-use crate::numeric::Numeric;
 use float_cmp::approx_eq;
 
 // Currently the four basic operations are supported (excluding the base operator, which is a base
 // operator for leaf nodes with no children). All operations must be performed with either one or
-// two children. To add an operator, the following must be implemented:
+// two children, except for reductions (such as `Sum` and `Mean`), which take any number of
+// children. To add an operator, the following must be implemented:
 //  1. The operator must be added to the enum below.
-//  2. The formatting of the operator must be defined in `Operation`'s `Display` impl.
+//  2. The formatting of the operator must be defined in `Operation`'s `Display` impl, and (unless
+//     its symbol is shared with another operator) parsed back in its `FromStr` impl.
 //  3. The actual functionality of the operator must be defined (either by overriding a default
-//     operator or creating a new one)
+//     operator or creating a new one), and repeated in `evaluate`, which recomputes it when a
+//     recorded tape is replayed.
 //  4. The derivative for the operator must be specified. That is, for some one-child operation
 //     z(x), ∂z/∂x must be defined, and for a two-child operation z(x, y), ∂z/∂x and ∂z/∂y must be
 //     defined.
 //  5. (Optional, but recommended) add tests for both the operator's functionality and derivative.
+/// The operation that produced a Scalar from its children.
 #[derive(Debug, PartialEq, Eq, Ord, PartialOrd, Clone, Copy)]
-enum Operation {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Operation {
+    /// Addition of two Scalars, `a + b`.
     Add,
+    /// Subtraction of two Scalars, `a - b`.
     Sub,
+    /// Multiplication of two Scalars, `a * b`.
     Mul,
+    /// Division of two Scalars, `a / b`.
     Div,
+    /// A Scalar raised to a constant power, `a^n`.
+    Pow,
+    /// The exponential function, `e^a`.
+    Exp,
+    /// The hyperbolic tangent, `tanh(a)`.
+    Tanh,
+    /// The rectified linear unit, `max(0, a)`.
+    ReLU,
+    /// The logistic sigmoid, `1 / (1 + e^-a)`.
+    Sigmoid,
+    /// Negation of a Scalar, `-a`.
+    Neg,
+    /// The square root, `√a`.
+    Sqrt,
+    /// The sum of any number of Scalars, `a + b + ...`.
+    Sum,
+    /// The mean of any number of Scalars, `(a + b + ...) / n`.
+    Mean,
+    /// Exponentiation by another Scalar, `a^b`.
+    PowVar,
+    /// The absolute value, `|a|`.
+    Abs,
+    /// The larger of two Scalars, `max(a, b)`.
+    Max,
+    /// The smaller of two Scalars, `min(a, b)`.
+    Min,
+    /// The sine, `sin(a)`.
+    Sin,
+    /// The cosine, `cos(a)`.
+    Cos,
+    /// Exponentiation by a constant integer, `a^n`.
+    Powi,
+    /// The reciprocal of a Scalar, `1 / a`.
+    Recip,
+    /// The hyperbolic sine, `sinh(a)`.
+    Sinh,
+    /// The hyperbolic cosine, `cosh(a)`.
+    Cosh,
+    /// The natural logarithm, `ln(a)`.
+    Ln,
+    /// No operation at all; used for leaf Scalars, which have no children.
     Base,
 }
 
@@ -42,6 +113,26 @@ impl Display for Operation {
             Operation::Sub => "-",
             Operation::Mul => "*",
             Operation::Div => "/",
+            Operation::Pow => "^",
+            Operation::Exp => "exp",
+            Operation::Tanh => "tanh",
+            Operation::ReLU => "relu",
+            Operation::Sigmoid => "sigmoid",
+            Operation::Neg => "neg",
+            Operation::Sqrt => "sqrt",
+            Operation::Sum => "sum",
+            Operation::Mean => "mean",
+            Operation::PowVar => "^",
+            Operation::Abs => "abs",
+            Operation::Max => "max",
+            Operation::Min => "min",
+            Operation::Sin => "sin",
+            Operation::Cos => "cos",
+            Operation::Powi => "^",
+            Operation::Recip => "recip",
+            Operation::Sinh => "sinh",
+            Operation::Cosh => "cosh",
+            Operation::Ln => "ln",
             Operation::Base => "BASE",
         };
 
@@ -49,38 +140,207 @@ impl Display for Operation {
     }
 }
 
-trait Derivable {
-    fn derive(&mut self);
-    fn backward(&mut self);
-}
-// Default derivation of `Clone`, while the `Derivative` crate allows for more advanced derivations
-// of `PartialEq` and `Eq` (in this case, allows us to ignore the `_label` field when comparing two
-// Scalars)
-#[derive(Derivative, Clone)]
-// Allows for more advanced derivations
-#[derivative(PartialEq, Eq, PartialOrd, Ord)]
-struct Scalar<'a> {
-    data_sign: i8,
-    data_int: u32,
-    data_frac: u32,
-    data_digits: u32,
-    _children: Vec<&'a Self>,
-    _grad_sign: Cell<i8>,
-    _grad_int: Cell<u32>,
-    _grad_frac: Cell<u32>,
-    _grad_digits: Cell<u32>,
+/// The error returned when parsing a string that is not the symbol of any `Operation`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseOperationError(String);
+
+impl Display for ParseOperationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unknown operation `{}`", self.0)
+    }
+}
+
+impl std::error::Error for ParseOperationError {}
+
+/// The error returned by checked arithmetic (such as `try_div`) when the result is not finite.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArithError {
+    /// The operation gave NaN, as in `0 / 0`.
+    NaN(Operation),
+    /// The operation gave positive or negative infinity, as in `1 / 0`, or overflowed.
+    Infinite(Operation),
+}
+
+impl Display for ArithError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArithError::NaN(op) => write!(f, "`{}` gave NaN", op),
+            ArithError::Infinite(op) => write!(f, "`{}` gave an infinite result", op),
+        }
+    }
+}
+
+impl std::error::Error for ArithError {}
+
+/// Parses an operation from the symbol its `Display` implementation prints, so that
+/// `"*".parse::<Operation>()` gives `Operation::Mul`. `Pow`, `PowVar` and `Powi` are all displayed
+/// as `^`, which parses as `Pow`.
+impl FromStr for Operation {
+    type Err = ParseOperationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let op = match s.trim() {
+            "+" => Operation::Add,
+            "-" => Operation::Sub,
+            "*" => Operation::Mul,
+            "/" => Operation::Div,
+            "^" => Operation::Pow,
+            "exp" => Operation::Exp,
+            "tanh" => Operation::Tanh,
+            "relu" => Operation::ReLU,
+            "sigmoid" => Operation::Sigmoid,
+            "neg" => Operation::Neg,
+            "sqrt" => Operation::Sqrt,
+            "sum" => Operation::Sum,
+            "mean" => Operation::Mean,
+            "abs" => Operation::Abs,
+            "max" => Operation::Max,
+            "min" => Operation::Min,
+            "sin" => Operation::Sin,
+            "cos" => Operation::Cos,
+            "recip" => Operation::Recip,
+            "sinh" => Operation::Sinh,
+            "cosh" => Operation::Cosh,
+            "ln" => Operation::Ln,
+            "BASE" => Operation::Base,
+            other => return Err(ParseOperationError(other.to_string())),
+        };
+
+        Ok(op)
+    }
+}
+
+/// Backpropagation over a computation graph.
+pub trait Derivable {
+    /// Propagates this node's gradient into the gradients of its direct children.
+    fn derive(&self);
+    /// Computes the gradient of this node with respect to every node it was computed from. Any
+    /// gradients left in the graph by an earlier backward pass are cleared first, so calling this
+    /// twice gives the same gradients as calling it once.
+    fn backward(&self);
+    /// Like `backward`, but adds onto whatever gradients the leaves of the graph already hold
+    /// instead of clearing them first. This is for summing the gradients of several outputs that
+    /// share inputs (for instance, the losses of the examples in a batch); `zero_grad_all` resets
+    /// them. Scalars computed from others are still cleared, and only hold the gradient from the
+    /// latest pass.
+    fn backward_accumulate(&self);
+    /// Calls `backward_accumulate` if `accumulate` is set, and `backward` otherwise, for when
+    /// whether to accumulate is decided at runtime (for instance, by the position of an example in
+    /// its mini-batch).
+    fn backward_into(&self, accumulate: bool) {
+        if accumulate {
+            self.backward_accumulate();
+        } else {
+            self.backward();
+        }
+    }
+}
+
+/// The constant parameters of the operation that produced a Scalar, for the operations that have
+/// any. These are kept apart from `Operation` itself, which stays a plain list of operations that
+/// can be compared, ordered and parsed regardless of the Scalar's float type.
+///
+/// ```
+/// use minigrad::{OpParams, Scalar};
+///
+/// let a = Scalar::new(2.0, "a");
+/// match a.pow(1.5).params() {
+///     OpParams::Pow { exponent } => assert_eq!(exponent, 1.5),
+///     _ => unreachable!(),
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub enum OpParams<F: Float> {
+    /// The operation has no constant parameters, as for every operation other than those below.
+    None,
+    /// The exponent of an `Operation::Pow` node.
+    Pow {
+        /// The constant power the child was raised to.
+        exponent: F,
+    },
+    /// The exponent of an `Operation::Powi` node.
+    Powi {
+        /// The constant integer power the child was raised to.
+        exponent: i32,
+    },
+}
+
+/// A single numeric value which remembers the operations that produced it, so that gradients can
+/// be computed with respect to it. Most code should use the [`Scalar`] alias rather than naming
+/// this type directly.
+///
+/// Scalars are compared by value: `==` requires the data, gradient, operation and children of
+/// both Scalars to match (but not their labels), and `<` and friends order Scalars by their data
+/// first, falling back to the rest only to break ties. Since NaN is not ordered against anything,
+/// comparing a Scalar holding NaN gives `None`; `total_cmp` orders every Scalar, NaN included, by
+/// its data alone, and is the better choice for sorting. As with floats, a Scalar holding `-0.0`
+/// equals one holding `0.0`; the sign of zero is kept (so dividing by either gives the infinity of
+/// the matching sign), but `total_cmp` is the only comparison that tells them apart.
+///
+/// A Scalar is a cheap handle to a node in the computation graph: cloning it (or using it in an
+/// expression) shares the node rather than copying it, so a gradient computed through one handle
+/// is visible through every other. Since each node owns its children, expressions can be built in
+/// loops and returned from functions without any of their operands needing to outlive them.
+///
+/// Scalars are `Send` and `Sync`, so separate parts of a graph can be built (and even
+/// backpropagated with `backward_accumulate`) on separate threads. Gradients flowing into a shared
+/// Scalar from several threads at once are all added up, but a graph should not be backpropagated
+/// through while another thread is still building on top of it or resetting its gradients.
+#[derive(Clone, PartialEq, PartialOrd)]
+pub struct GenericScalar<F: Float> {
+    inner: Arc<ScalarNode<F>>,
+}
+
+// The `Derivative` crate allows for more advanced derivations of `PartialEq` (in this case, allows
+// us to ignore the `_label` field when comparing two Scalars)
+#[derive(Derivative)]
+#[derivative(PartialEq, PartialOrd)]
+struct ScalarNode<F: Float> {
+    // Kept in an `AtomicFloat` so that optimizers can update parameters in place.
+    data: AtomicFloat<F>,
+    _children: Vec<GenericScalar<F>>,
+    _grad: AtomicFloat<F>,
     _op: Operation,
-    #[derivative(PartialEq = "ignore")]
-    _label: &'static str,
+    // The constant parameters of the operation, such as the exponent of `Operation::Pow` and
+    // `Operation::Powi` nodes. Since they are constants, they cannot be children (which would also
+    // receive gradients), so they are kept on the resulting node instead.
+    _params: OpParams<F>,
+    // Kept in a `SharedLabel` so that a Scalar can be renamed even while its node is shared.
+    #[derivative(PartialEq = "ignore", PartialOrd = "ignore")]
+    _label: SharedLabel,
+    // Whether optimizers should update this Scalar. False for constants, and for any Scalar
+    // computed only from constants.
+    #[derivative(PartialEq = "ignore", PartialOrd = "ignore")]
+    _requires_grad: bool,
+}
+
+// Dropping a node drops its children, which would recurse as deep as the graph is if left to the
+// default drop glue. Instead, children that are about to be freed have their own children moved
+// onto an explicit stack, so that dropping a long chain never overflows the call stack.
+impl<F: Float> Drop for ScalarNode<F> {
+    fn drop(&mut self) {
+        let mut stack = std::mem::take(&mut self._children);
+        while let Some(child) = stack.pop() {
+            if let Ok(mut node) = Arc::try_unwrap(child.inner) {
+                stack.append(&mut node._children);
+            }
+        }
+    }
 }
 
-impl Debug for Scalar<'_> {
+/// A Scalar storing its data and gradient as `f32`s, which is precise enough for most uses.
+pub type Scalar = GenericScalar<f32>;
+
+/// A Scalar storing its data and gradient as `f64`s, for when more precision is needed.
+pub type Scalar64 = GenericScalar<f64>;
+
+impl<F: Float> Debug for GenericScalar<F> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut child_str = String::new();
-        for c in &self._children[..] {
+        for c in &self.inner._children[..] {
             child_str.push_str(&format!(
                 "Scalar(label = {}, data = {}, grad = {}), ",
-                c._label,
+                c.inner._label.get(),
                 c.join_data(),
                 c.join_grad()
             ));
@@ -88,459 +348,3857 @@ impl Debug for Scalar<'_> {
 
         write!(
             f,
-            "Scalar(label = {}, data = {}, grad = {}, children = [{}], operation = {})",
-            self._label,
+            "Scalar(label = {}, data = {}, grad = {}, children = [{}], operation = {}",
+            self.inner._label.get(),
             self.join_data(),
             self.join_grad(),
             child_str,
-            self._op
-        )
+            self.inner._op
+        )?;
+        match self.inner._params {
+            OpParams::None => (),
+            OpParams::Pow { exponent } => write!(f, ", exponent = {}", exponent)?,
+            OpParams::Powi { exponent } => write!(f, ", exponent = {}", exponent)?,
+        }
+        write!(f, ")")
     }
 }
 
-impl Scalar<'_> {
-    fn split_f32(x: f32) -> (i8, u32, u32, u32) {
-        let sign = if x < 0. { -1i8 } else { 1i8 };
+/// Parses a leaf Scalar from a float literal, such as `"2.5"` or `"-1e-3"`, ignoring surrounding
+/// whitespace. The resulting Scalar has no label.
+///
+/// ```
+/// use minigrad::Scalar;
+///
+/// let a: Scalar = "2.5".parse().unwrap();
+/// assert_eq!(a.data(), 2.5);
+/// assert!("abc".parse::<Scalar>().is_err());
+/// ```
+impl<F: Float + FromStr<Err = ParseFloatError>> FromStr for GenericScalar<F> {
+    type Err = ParseFloatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let data = s.trim().parse::<F>()?;
+        Ok(GenericScalar::new_op(data, vec![], Operation::Base, ""))
+    }
+}
 
-        if x == x.floor() {
-            return (sign, x.abs() as u32, 0, 1);
+// Renders the expression that produced a Scalar in infix notation, such as `((a + b) * 2)`. Leaves
+// are shown by their label, or by their data if they have none (as constants do).
+impl<F: Float> Display for GenericScalar<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let children = &self.inner._children;
+        match self.inner._op {
+            Operation::Base => match self.inner._label.get() {
+                "" => write!(f, "{}", self.join_data()),
+                label => write!(f, "{}", label),
+            },
+            Operation::Add
+            | Operation::Sub
+            | Operation::Mul
+            | Operation::Div
+            | Operation::PowVar => {
+                write!(f, "({} {} {})", children[0], self.inner._op, children[1])
+            }
+            Operation::Pow | Operation::Powi => match self.inner._params {
+                OpParams::Pow { exponent } => write!(f, "({} ^ {})", children[0], exponent),
+                OpParams::Powi { exponent } => write!(f, "({} ^ {})", children[0], exponent),
+                OpParams::None => write!(f, "({} ^ ?)", children[0]),
+            },
+            Operation::Neg => write!(f, "(-{})", children[0]),
+            // Every other operation is written as a function applied to its children.
+            op => {
+                write!(f, "{}(", op)?;
+                for (i, child) in children.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", child)?;
+                }
+                write!(f, ")")
+            }
         }
+    }
+}
+
+/// The size and shape of a computation graph, as returned by `graph_stats`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GraphStats {
+    /// The number of distinct Scalars in the graph, including the root.
+    pub nodes: usize,
+    /// The number of links from a Scalar to one of its children. A child used twice by the same
+    /// Scalar (as in `&a * &a`) counts twice.
+    pub edges: usize,
+    /// The number of operations on the longest path from the root down to a leaf, so a leaf on its
+    /// own has a depth of 0.
+    pub depth: usize,
+}
 
-        let s = x.to_string();
-        let mut spl = s.split('.');
+impl<F: Float> GenericScalar<F> {
+    // Data and gradients are stored directly as floats (rather than decomposed into digits, as in
+    // earlier versions), so reading either is a single atomic load, with nothing to reconstruct or
+    // cache.
+    fn join_data(&self) -> F {
+        self.inner.data.get()
+    }
 
-        let mut int: &str = "";
-        let mut frac: &str = "";
+    fn join_grad(&self) -> F {
+        self.inner._grad.get()
+    }
 
-        while let Some(s) = spl.next() {
-            if int.is_empty() {
-                int = s;
-            } else {
-                frac = s;
-            }
-        }
+    /// Returns the data stored in this Scalar.
+    pub fn data(&self) -> F {
+        self.join_data()
+    }
+
+    /// Returns the single value held by this Scalar, like `item` in PyTorch. A Scalar always holds
+    /// exactly one value, so for now this is the same as `data`.
+    ///
+    /// ```
+    /// use minigrad::Scalar;
+    ///
+    /// let a = Scalar::new(1.5, "a");
+    /// let b = &a * 2.0;
+    /// assert_eq!(b.item(), 3.0);
+    /// ```
+    pub fn item(&self) -> F {
+        self.join_data()
+    }
+
+    /// Returns the operation that produced this Scalar, or `Operation::Base` for a leaf.
+    pub fn op(&self) -> Operation {
+        self.inner._op
+    }
+
+    /// Returns the Scalars this Scalar was computed from, in the order they were passed to its
+    /// operation. Constants (such as the `2` in `&a * 2`) appear as unlabelled leaves. Leaves have
+    /// no children.
+    pub fn children(&self) -> &[GenericScalar<F>] {
+        &self.inner._children
+    }
+
+    /// Returns the label of this Scalar, which is empty if it was never given one.
+    pub fn label(&self) -> &'static str {
+        self.inner._label.get()
+    }
+
+    /// Returns the gradient of this Scalar, as computed by the last call to `backward` on a Scalar
+    /// that depends on it.
+    ///
+    /// ```
+    /// use minigrad::{Derivable, Scalar};
+    ///
+    /// let a = Scalar::new(3.0, "a");
+    /// let c = &a + &a;
+    /// c.backward();
+    /// assert_eq!(a.grad(), 2.0);
+    /// ```
+    pub fn grad(&self) -> F {
+        self.join_grad()
+    }
+
+    /// Checks whether the data of this Scalar and `other` are at most `ulps` representable values
+    /// apart. Unlike `==`, which requires the data to be exactly equal and also compares the
+    /// gradients, operations and children of both Scalars, this compares the data alone (so, for
+    /// instance, `0.1 + 0.2` approximately equals a leaf holding `0.3`).
+    pub fn approx_eq(&self, other: &Self, ulps: u32) -> bool {
+        self.join_data().ulps_eq(other.join_data(), ulps)
+    }
+
+    /// Orders this Scalar and `other` by their data alone, using IEEE 754's total order, in which
+    /// NaN sorts after +∞ (and -NaN before -∞). Unlike `partial_cmp`, this never fails, so it can
+    /// be used to sort Scalars even when some hold NaN: `scalars.sort_by(Scalar::total_cmp)`.
+    pub fn total_cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.join_data().total_cmp(&other.join_data())
+    }
+
+    /// Creates a leaf Scalar holding `data`. The data is rounded to the nearest value representable
+    /// by `F`, so only around 7 significant digits are kept for an `f32` (and 16 for an `f64`);
+    /// any digits beyond that are silently dropped rather than causing an error.
+    pub fn new(data: impl Numeric, label: &'static str) -> Self {
+        GenericScalar::new_op(data.to_float(), vec![], Operation::Base, label)
+    }
 
-        // Ignore a preceding negative sign (if it exists). The following is used instead of a
-        // `int.replace("-", "")` because a negative sign should only be at the beginning of the
-        // number. Anywhere else and we should panic.
-        if &int[0..1] == "-" {
-            int = &int[1..];
+    /// Like `new`, but returns `NumericError::Overflow` if `data` is finite yet too large for `F`
+    /// to hold, rather than silently storing infinity (as `new` does for, say, `u128::MAX` in a
+    /// `Scalar`). Rounding is still allowed, since almost every value is rounded to some degree.
+    ///
+    /// ```
+    /// use minigrad::{NumericError, Scalar, Scalar64};
+    ///
+    /// assert_eq!(Scalar::try_new(u32::MAX as i64 * 10, "x").unwrap().data(), 42_949_673_000.0);
+    /// assert_eq!(Scalar::try_new(u128::MAX, "x"), Err(NumericError::Overflow));
+    /// assert!(Scalar64::try_new(u128::MAX, "x").is_ok());
+    /// ```
+    pub fn try_new(data: impl Numeric, label: &'static str) -> Result<Self, NumericError> {
+        let float: F = data.to_float();
+        let finite = data.to_f64().is_some_and(f64::is_finite);
+        if float.is_infinite() && finite {
+            return Err(NumericError::Overflow);
         }
 
-        (
-            sign,
-            int.parse::<u32>()
-                .expect("The integral (whole) number before the decimal point should be valid!"),
-            frac.parse::<u32>()
-                .expect("The fractional part after the decimal point should be valid!"),
-            frac.len() as u32,
-        )
+        Ok(GenericScalar::new_op(float, vec![], Operation::Base, label))
     }
 
-    fn join_f32(sign: i8, int: u32, frac: u32, n_digits: u32) -> f32 {
-        (sign as f32) * (int as f32 + frac as f32 / 10f32.powi(n_digits as i32))
+    /// Creates an unlabelled leaf Scalar holding `data`, for when a name isn't needed. Otherwise
+    /// identical to `new`.
+    ///
+    /// ```
+    /// use minigrad::Scalar;
+    ///
+    /// let a = Scalar::scalar(3.0);
+    /// assert_eq!(a.data(), 3.0);
+    /// ```
+    pub fn scalar(data: impl Numeric) -> Self {
+        GenericScalar::new(data, "")
     }
 
-    fn join_data(&self) -> f32 {
-        Self::join_f32(
-            self.data_sign,
-            self.data_int,
-            self.data_frac,
-            self.data_digits,
-        )
+    /// Names this Scalar, returning it so that the results of expressions can be labelled as they
+    /// are created. The label shows up in this Scalar's `Debug` output and in `to_dot`.
+    ///
+    /// ```
+    /// use minigrad::Scalar;
+    ///
+    /// let a = Scalar::new(1.0, "a");
+    /// let b = Scalar::new(2.0, "b");
+    /// let c = (&a + &b).with_label("c");
+    /// assert!(format!("{:?}", c).starts_with("Scalar(label = c,"));
+    /// ```
+    pub fn with_label(mut self, label: &'static str) -> Self {
+        self.set_label(label);
+        self
+    }
+
+    /// Renames this Scalar in place. See `with_label`. Every handle sharing this Scalar's node sees
+    /// the new label.
+    pub fn set_label(&mut self, label: &'static str) {
+        self.inner._label.set(label);
     }
 
-    fn join_grad(&self) -> f32 {
-        Self::join_f32(
-            self._grad_sign.get(),
-            self._grad_int.get(),
-            self._grad_frac.get(),
-            self._grad_digits.get(),
+    /// Returns a new leaf Scalar with the same data and label as this one, but none of its history.
+    /// Since the result has no children, `backward` treats it as a constant, and no gradient flows
+    /// back through it into this Scalar (the equivalent of `stop_gradient` or `detach` elsewhere).
+    pub fn detach(&self) -> Self {
+        GenericScalar::new_op(
+            self.join_data(),
+            vec![],
+            Operation::Base,
+            self.inner._label.get(),
         )
     }
 
-    fn new(data: impl Numeric, label: &'static str) -> Self {
-        let (data_sign, data_int, data_frac, data_digits) = Self::split_f32(data.to_f32());
+    /// Creates a Scalar computed by `_op` from `_ch`, with a gradient of zero (as every Scalar
+    /// starts with until `backward` is called).
+    fn new_op(data: F, _ch: Vec<&Self>, _op: Operation, label: &'static str) -> Self {
+        GenericScalar::new_full(data, _ch, F::zero(), _op, label)
+    }
+
+    /// Like `new_op`, but with an initial gradient of `_grad`.
+    fn new_full(data: F, _ch: Vec<&Self>, _grad: F, _op: Operation, label: &'static str) -> Self {
+        let _children: Vec<GenericScalar<F>> = _ch.into_iter().cloned().collect();
+        // Leaves require gradients unless they are made with `constant`.
+        let _requires_grad = _children.is_empty() || _children.iter().any(|c| c.requires_grad());
 
-        Scalar {
-            data_sign,
-            data_int,
-            data_frac,
-            data_digits,
-            _children: vec![],
-            _grad_sign: Cell::new(1i8),
-            _grad_int: Cell::new(0u32),
-            _grad_frac: Cell::new(0u32),
-            _grad_digits: Cell::new(0u32),
-            _op: Operation::Base,
-            _label: label,
+        GenericScalar {
+            inner: Arc::new(ScalarNode {
+                data: AtomicFloat::new(data),
+                _children,
+                _grad: AtomicFloat::new(_grad),
+                _op,
+                _params: OpParams::None,
+                _label: SharedLabel::new(label),
+                _requires_grad,
+            }),
         }
     }
 
-    fn new_full<'a>(
-        data: impl Numeric,
-        _ch: Vec<&'a Self>,
-        _grad: f32,
-        _op: Operation,
-        label: &'static str,
-    ) -> Scalar<'a> {
-        let (data_sign, data_int, data_frac, data_digits) = Self::split_f32(data.to_f32());
-        let (_grad_sign, _grad_int, _grad_frac, _grad_digits) = Scalar::split_f32(_grad);
+    /// Creates a constant leaf Scalar holding `data`, such as a fixed target value or
+    /// hyperparameter. Optimizers leave the data of constants untouched, and `backward` does not
+    /// propagate gradients through any Scalar computed only from constants (though such a Scalar
+    /// still receives its own gradient from the Scalar using it). Constants used directly in
+    /// expressions (like the `2` in `&a * 2`) are made this way too.
+    pub fn constant(data: impl Numeric) -> Self {
+        let mut out = GenericScalar::new(data, "");
+        Arc::get_mut(&mut out.inner)
+            .expect("A newly created Scalar should not be shared yet!")
+            ._requires_grad = false;
+
+        out
+    }
+
+    /// Returns whether this Scalar should be updated by optimizers: true for leaves made with `new`
+    /// or `scalar`, false for those made with `constant`, and true for any other Scalar exactly
+    /// when one of its children requires gradients.
+    pub fn requires_grad(&self) -> bool {
+        self.inner._requires_grad
+    }
+
+    // Sets the constant parameters of this Scalar's operation (such as the exponent of a `Pow`
+    // node). Only valid straight after the node is created, before any other Scalar has had the
+    // chance to share it.
+    fn set_params(&mut self, params: OpParams<F>) {
+        Arc::get_mut(&mut self.inner)
+            .expect("Parameters should only be set on a newly created Scalar!")
+            ._params = params;
+    }
+
+    /// Returns the constant parameters of the operation that produced this Scalar, such as the
+    /// exponent of `pow`, or `OpParams::None` if it has none.
+    pub fn params(&self) -> OpParams<F> {
+        self.inner._params
+    }
 
-        Scalar {
-            data_sign,
-            data_int,
-            data_frac,
-            data_digits,
-            _children: _ch,
-            _grad_sign: Cell::new(_grad_sign),
-            _grad_int: Cell::new(_grad_int),
-            _grad_frac: Cell::new(_grad_frac),
-            _grad_digits: Cell::new(_grad_digits),
-            _op,
-            _label: label,
+    fn pow_exponent(&self) -> F {
+        match self.inner._params {
+            OpParams::Pow { exponent } => exponent,
+            _ => panic!("A `Pow` node should always store its exponent!"),
         }
     }
 
-    fn update_grad(&self, new_grad: f32) {
-        let (new_grad_sign, new_grad_int, new_grad_frac, new_grad_digits): (i8, u32, u32, u32) =
-            Self::split_f32(new_grad);
+    fn powi_exponent(&self) -> i32 {
+        match self.inner._params {
+            OpParams::Powi { exponent } => exponent,
+            _ => panic!("A `Powi` node should always store its integer exponent!"),
+        }
+    }
 
-        (*self)._grad_sign.set(new_grad_sign);
-        (*self)._grad_int.set(new_grad_int);
-        (*self)._grad_frac.set(new_grad_frac);
-        (*self)._grad_digits.set(new_grad_digits);
+    // Overwrites the gradient of this Scalar. Only used to seed or reset gradients; `derive` adds
+    // its contributions with `accumulate_grad` instead.
+    fn set_grad(&self, new_grad: F) {
+        self.inner._grad.set(new_grad);
     }
-}
 
-impl Derivable for Scalar<'_> {
-    fn derive(&mut self) {
-        let _parent_grad = self.join_grad();
+    // Adds `delta` onto the gradient of this Scalar, which is how every gradient contribution
+    // flowing back from a parent is applied. The addition is a single atomic step, so no
+    // contribution is lost even if several threads add onto the same gradient at once.
+    fn accumulate_grad(&self, delta: F) {
+        self.inner._grad.add(delta);
+    }
 
-        if self._children.len() == 0 {
-            return;
+    // Overwrites the data of this Scalar. Any Scalar already computed from this one keeps its old
+    // data, so graphs should be rebuilt after their leaves are updated.
+    fn update_data(&self, new_data: F) {
+        self.inner.data.set(new_data);
+    }
+
+    /// Resets the gradient of this Scalar (and only this Scalar) to zero.
+    pub fn zero_grad(&self) {
+        self.set_grad(F::zero());
+    }
+
+    /// Resets the gradient of this Scalar and of every Scalar it was computed from to zero.
+    /// `backward` already does this before computing gradients, but gradients accumulate across
+    /// calls to `backward_accumulate`, so this should be called between such accumulated passes
+    /// (for instance, between the batches of a training loop).
+    pub fn zero_grad_all(&self) {
+        for node in parse_topology(self, false) {
+            node.zero_grad();
         }
+    }
 
-        let orig_grad0 = self._children[0].join_grad();
-        let orig_grad1 = self._children[1].join_grad();
+    /// Returns the gradient of `wrt`, as long as it is part of the graph rooted at this Scalar (for
+    /// instance, a parameter this Scalar was computed from), or `None` if it is not. `wrt` is found
+    /// by identity, so a different Scalar that merely holds the same data does not count.
+    ///
+    /// ```
+    /// use minigrad::{Derivable, Scalar};
+    ///
+    /// let params = vec![Scalar::new(2.0, "w"), Scalar::new(3.0, "b")];
+    /// let y = &params[0] * &params[1];
+    /// y.backward();
+    /// assert_eq!(y.grad_of(&params[0]), Some(3.0));
+    /// assert_eq!(y.grad_of(&Scalar::new(2.0, "w")), None);
+    /// ```
+    pub fn grad_of(&self, wrt: &Self) -> Option<F> {
+        parse_topology(self, false)
+            .into_iter()
+            .find(|node| Arc::ptr_eq(&node.inner, &wrt.inner))
+            .map(|node| node.join_grad())
+    }
 
-        match self._op {
-            // For each of the following operations, let z be the final output value produced by
-            // the overall computation, a (and b, if applicable) be the current child nodes being
-            // processed, and y be the output of applying the found operation to those nodes.
-            Operation::Add => {
-                // Here, we have y = a + b. The following holds:
-                //  1. ∂y/∂a = 1.0, and therefore, ∂z/∂a = ∂z/∂y
-                self._children[0].update_grad(orig_grad0 + _parent_grad);
-                //  2. ∂y/∂b = 1.0, and therefore, ∂z/∂b = ∂z/∂y
-                self._children[1].update_grad(orig_grad1 + _parent_grad);
-            }
-            Operation::Sub => {
-                // Here, we have y = a - b. The following holds:
-                //  1. ∂y/∂a = 1.0, and therefore, ∂z/∂a = ∂z/∂y
-                self._children[0].update_grad(orig_grad0 + _parent_grad);
-                //  2. ∂y/∂b = -1.0, and therefore, ∂z/∂b = -1.0 * ∂z/∂y
-                self._children[1].update_grad(orig_grad1 + _parent_grad * -1.);
+    /// Collects the data of every labelled Scalar in the graph rooted at this one (including this
+    /// Scalar itself), keyed by label, so that intermediate values can be inspected by name.
+    /// Unlabelled Scalars are left out. If several Scalars share a label, the one closest to this
+    /// Scalar (in the order of `topo_order`) is kept.
+    ///
+    /// ```
+    /// use minigrad::Scalar;
+    ///
+    /// let x = Scalar::new(2.0, "x");
+    /// let hidden = (&x * 3.0).with_label("hidden");
+    /// let out = hidden.tanh().with_label("out");
+    ///
+    /// let values = out.values_by_label();
+    /// assert_eq!(values["hidden"], 6.0);
+    /// assert_eq!(values["out"], 6f32.tanh());
+    /// ```
+    pub fn values_by_label(&self) -> HashMap<&'static str, F> {
+        let mut values = HashMap::new();
+        for node in parse_topology(self, false) {
+            let label = node.inner._label.get();
+            if !label.is_empty() {
+                values.entry(label).or_insert_with(|| node.join_data());
             }
-            Operation::Mul => {
-                // We will need access to `a` and `b` to calculate the derivatives, unlike the
-                // previous operations.
-                let orig_data0 = self._children[0].join_data(); // Represents `a` here
-                let orig_data1 = self._children[1].join_data(); // Represents `b` here
+        }
 
-                // Here, we have y = ab. The following holds:
-                //  1. ∂y/∂a = b, and therefore, ∂z/∂a = ∂z/∂y * b
-                self._children[0].update_grad(orig_grad0 + _parent_grad * orig_data1);
-                //  2. ∂y/∂b = a, and therefore, ∂z/∂b = ∂z/∂y * a
-                self._children[1].update_grad(orig_grad1 + _parent_grad * orig_data0);
-            }
-            Operation::Div => {
-                // We will need access to `a` and `b` here as well.
-                let orig_data0 = self._children[0].join_data();
-                let orig_data1 = self._children[1].join_data();
+        values
+    }
 
-                // Here, we have y = a ÷ b, or y = 1/b * a. The following holds:
-                //  1. ∂y/∂a = 1/b, and therefore, ∂z/∂a = ∂z/∂y * 1/b
-                (*self._children[0]).update_grad(orig_grad0 + _parent_grad * 1. / orig_data1);
-                //  2. ∂y/∂b = -a * b^-2, and therefore, ∂z/∂b = -∂z/∂y * (a/b^2)
-                self._children[1]
-                    .update_grad(orig_grad1 - _parent_grad * orig_data0 / orig_data1.powi(2));
-            }
-            // TODO: Implement more operations here
-            _ => (), // The only other case here is the Base operation, which is just the default
-                     // for leaf nodes, so no need to handle those (leaf nodes have no children).
+    /// Replaces the data of this Scalar with `value`, through every handle sharing it. Scalars
+    /// already computed from this one keep their old data until `recompute` is called on them, so
+    /// this is mostly useful for leaves (such as the inputs of a graph that is reused).
+    pub fn set_data(&self, value: impl Numeric) {
+        self.update_data(value.to_float());
+    }
+
+    /// Recomputes the data of every Scalar in the graph rooted at this one from its children,
+    /// starting from the leaves, so that changes made to the leaves with `set_data` reach this
+    /// Scalar without rebuilding the graph. Gradients are left as they were, so `backward` should
+    /// be called again afterwards if they are needed.
+    ///
+    /// ```
+    /// use minigrad::Scalar;
+    ///
+    /// let x = Scalar::new(3.0, "x");
+    /// let y = &x * &x;
+    ///
+    /// x.set_data(4.0);
+    /// assert_eq!(y.data(), 9.0);
+    /// y.recompute();
+    /// assert_eq!(y.data(), 16.0);
+    /// ```
+    pub fn recompute(&self) {
+        for node in parse_topology(self, false).into_iter().rev() {
+            node.update_data(node.evaluate());
         }
     }
 
-    /// Given a Scalar, takes its derivative and the derivative of all its children (direct or
-    /// indirect) in a recursive fashion, until every node in the Scalar's
-    fn backward(&mut self) {
-        self.update_grad(1.0);
+    /// Returns every Scalar in the graph rooted at this one, in the order `backward` processes them:
+    /// this Scalar first, and every other Scalar before all of the Scalars it was computed from.
+    /// Each Scalar appears once, however many times it is used. Unlike `backward`, this includes
+    /// Scalars computed only from constants.
+    pub fn topo_order(&self) -> Vec<&GenericScalar<F>> {
+        parse_topology(self, false)
+    }
+
+    /// Returns every learnable leaf in the graph rooted at this one, in the order of `topo_order`:
+    /// the Scalars made with `new` or `scalar` that this Scalar was computed from, which are what an
+    /// optimizer should update. Constants (including those used directly in expressions, like the
+    /// `2` in `&a * 2`) are left out.
+    ///
+    /// ```
+    /// use minigrad::Scalar;
+    ///
+    /// let w = Scalar::new(2.0, "w");
+    /// let b = Scalar::new(1.0, "b");
+    /// let y = &(&w * 3.0) + &b;
+    ///
+    /// let labels: Vec<_> = y.parameters().iter().map(|p| p.label()).collect();
+    /// assert_eq!(labels.len(), 2);
+    /// assert!(labels.contains(&"w") && labels.contains(&"b"));
+    /// ```
+    pub fn parameters(&self) -> Vec<&GenericScalar<F>> {
+        parse_topology(self, false)
+            .into_iter()
+            .filter_map(|node| node.as_learnable_leaf())
+            .collect()
+    }
+
+    /// Returns this Scalar if it is a learnable leaf (one made with `new` or `scalar`, which an
+    /// optimizer may update in place), or `None` if it is a constant or was computed from other
+    /// Scalars. This is useful for filtering a hand-collected list of parameters, since updating
+    /// the data of an intermediate Scalar would leave it inconsistent with its children.
+    ///
+    /// ```
+    /// use minigrad::Scalar;
+    ///
+    /// let w = Scalar::new(2.0, "w");
+    /// let y = &w * 3.0;
+    /// assert!(w.as_learnable_leaf().is_some());
+    /// assert!(y.as_learnable_leaf().is_none());
+    /// assert!(Scalar::constant(3.0).as_learnable_leaf().is_none());
+    /// ```
+    pub fn as_learnable_leaf(&self) -> Option<&Self> {
+        (self.inner._op == Operation::Base && self.requires_grad()).then_some(self)
+    }
+
+    /// Renders the whole graph rooted at this Scalar as an indented tree, one Scalar per line, with
+    /// each Scalar's children below it and indented one level further. Unlike `Debug`, which only
+    /// shows the direct children, this shows every Scalar down to the leaves; a Scalar used in
+    /// several places is shown in each of them.
+    ///
+    /// ```
+    /// use minigrad::Scalar;
+    ///
+    /// let a = Scalar::new(1.0, "a");
+    /// let b = (-&a).with_label("b");
+    /// assert_eq!(
+    ///     b.debug_tree(),
+    ///     "Scalar(label = b, data = -1, grad = 0, operation = neg)\n  \
+    ///      Scalar(label = a, data = 1, grad = 0, operation = BASE)\n"
+    /// );
+    /// ```
+    pub fn debug_tree(&self) -> String {
+        let mut tree = String::new();
 
-        //let mut topology: Vec<Scalar<'_>> = Vec::new();
-        //let mut visited: Vec<Scalar<'_>> = Vec::new();
+        // An explicit stack (rather than recursion) keeps very deep graphs from overflowing the
+        // call stack. Children are pushed in reverse, so that they are rendered in order.
+        let mut stack = vec![(self, 0)];
+        while let Some((node, depth)) = stack.pop() {
+            tree.push_str(&format!(
+                "{:indent$}Scalar(label = {}, data = {}, grad = {}, operation = {})\n",
+                "",
+                node.inner._label.get(),
+                node.join_data(),
+                node.join_grad(),
+                node.inner._op,
+                indent = 2 * depth
+            ));
+            stack.extend(node.inner._children.iter().rev().map(|c| (c, depth + 1)));
+        }
 
-        //let mut topology = build_topo(&mut topology, &mut visited, self.clone());
-        let topology = parse_topology(self);
+        tree
+    }
 
-        dbg!(&topology);
+    /// Captures the label and current gradient of every learnable leaf (see `parameters`) in the
+    /// graph rooted at this Scalar, in the order of `topo_order`. Since the result is a copy, it
+    /// can be kept across training steps, for instance to log or plot gradient magnitudes.
+    ///
+    /// ```
+    /// use minigrad::{Derivable, Scalar};
+    ///
+    /// let w = Scalar::new(2.0, "w");
+    /// let y = &w * 3.0;
+    /// y.backward();
+    /// assert_eq!(y.grad_snapshot(), vec![("w", 3.0)]);
+    /// ```
+    pub fn grad_snapshot(&self) -> Vec<(&'static str, F)> {
+        self.parameters()
+            .into_iter()
+            .map(|node| (node.label(), node.join_grad()))
+            .collect()
+    }
 
-        for s in topology.iter() {
-            //&mut (*s).derive();
-            let node = s.borrow_mut();
-            node.clone().derive();
-            dbg!(&node);
+    /// Clips the gradient of this Scalar (and only this Scalar) so that its magnitude is at most
+    /// `max_norm`, keeping its sign.
+    pub fn clip_grad(&self, max_norm: F) {
+        let grad = self.join_grad();
+        if grad.abs() > max_norm {
+            self.set_grad(max_norm * grad.signum());
         }
     }
-}
 
-fn parse_topology<'a>(node: &'a Scalar<'a>) -> Vec<RefCell<&'a Scalar<'a>>> {
-    let mut topology: Vec<RefCell<&Scalar<'_>>> = vec![RefCell::new(node)];
-    let mut curr_level: Vec<&Scalar<'_>> = vec![node];
-    let mut visited: Vec<&Scalar<'_>> = vec![];
+    /// Rescales the gradients of every learnable leaf this Scalar was computed from (see
+    /// `parameters`), so that together (as a vector) their L2 norm is at most `max_norm`. Their
+    /// directions are preserved, and if the norm is already within the limit, nothing changes.
+    /// Constants are neither counted nor rescaled. This is usually called after `backward` and
+    /// before an optimizer step, to keep a single large gradient from destabilizing training.
+    pub fn clip_grad_all(&self, max_norm: F) {
+        let leaves = self.parameters();
 
-    while curr_level.len() > 0 {
-        for n in curr_level[0]._children.iter() {
-            if !visited.contains(&n) {
-                visited.push(n);
-                curr_level.push(n);
-                topology.push(RefCell::new(n));
+        let norm = leaves
+            .iter()
+            .fold(F::zero(), |total, leaf| total + leaf.join_grad().powi(2))
+            .sqrt();
+        if norm > max_norm {
+            let scale = max_norm / norm;
+            for leaf in leaves {
+                leaf.set_grad(leaf.join_grad() * scale);
             }
         }
-        curr_level.remove(0);
     }
 
-    topology
-}
+    /// Renders the graph rooted at this Scalar in Graphviz's DOT language, so that it can be
+    /// drawn with (for instance) `dot -Tsvg`. Every Scalar becomes a record showing its label,
+    /// data and gradient, and every operation becomes a separate node between a Scalar and its
+    /// children, much like micrograd's `draw_dot`.
+    pub fn to_dot(&self) -> String {
+        // Scalars are identified by the address of their node, since distinct Scalars may share a
+        // label, and handles to the same Scalar may live at different addresses.
+        fn id<F: Float>(node: &GenericScalar<F>) -> String {
+            format!("n{:p}", Arc::as_ptr(&node.inner))
+        }
 
-fn build_topo<'a>(
-    topo: &mut Vec<Scalar<'a>>,
-    visited: &mut Vec<Scalar<'a>>,
-    v: Scalar<'a>,
-) -> Vec<Scalar<'a>> {
-    assert_eq!(
-        &Scalar::new_full(3.0, vec![], 3.2, Operation::Add, "d"),
-        &Scalar::new_full(3.0, vec![], 3.2, Operation::Add, "e")
-    );
+        let mut dot = String::from("digraph {\n    rankdir = LR;\n");
+        for node in parse_topology(self, false) {
+            dot.push_str(&format!(
+                "    {} [shape = record, label = \"{{ {} | data {:.4} | grad {:.4} }}\"];\n",
+                id(node),
+                node.inner._label.get().replace('"', "\\\""),
+                node.join_data(),
+                node.join_grad()
+            ));
 
-    //if !visited.contains(v) {
-    if !visited.contains(&v) {
-        dbg!(&v);
-        visited.push(v.clone());
-        topo.push(v.clone());
-        for child in v._children.iter() {
-            let c_ = child.clone().clone();
-            build_topo(topo, visited, c_);
+            if node.inner._op != Operation::Base {
+                dot.push_str(&format!(
+                    "    {}_op [label = \"{}\"];\n    {}_op -> {};\n",
+                    id(node),
+                    node.inner._op,
+                    id(node),
+                    id(node)
+                ));
+                for child in node.inner._children.iter() {
+                    dot.push_str(&format!("    {} -> {}_op;\n", id(child), id(node)));
+                }
+            }
         }
+        dot.push('}');
+
+        dot
     }
 
-    topo.clone()
-}
+    /// Counts the Scalars and links in the graph rooted at this Scalar, and measures its depth.
+    ///
+    /// The graph can never contain a cycle: a Scalar's children are fixed when it is created, and
+    /// must already exist by then, so no Scalar can (even indirectly) be its own child. There is
+    /// therefore no cycle to detect or report.
+    pub fn graph_stats(&self) -> GraphStats {
+        let topology = parse_topology(self, false);
 
-impl<'a> ops::Add for &'a Scalar<'a> {
-    type Output = Scalar<'a>;
-    fn add(self, rhs: Self) -> Self::Output {
-        Scalar::<'a>::new_full(
-            self.join_data() + rhs.join_data(),
-            vec![self, rhs],
-            0.0,
-            Operation::Add,
+        // Children come after their parents in the topology, so walking it backwards finds the
+        // depth of every child before it is needed by a parent.
+        let mut depths: HashMap<*const ScalarNode<F>, usize> = HashMap::new();
+        let mut edges = 0;
+        for node in topology.iter().rev() {
+            let children = &node.inner._children;
+            edges += children.len();
+
+            let depth = children
+                .iter()
+                .map(|child| depths[&Arc::as_ptr(&child.inner)] + 1)
+                .max()
+                .unwrap_or(0);
+            depths.insert(Arc::as_ptr(&node.inner), depth);
+        }
+
+        GraphStats {
+            nodes: topology.len(),
+            edges,
+            depth: depths[&Arc::as_ptr(&self.inner)],
+        }
+    }
+
+    /// Raises this Scalar to a constant power, `exponent`. The exponent is stored on the resulting
+    /// node rather than as a second child, since it is a constant and has no gradient of its own.
+    pub fn pow(&self, exponent: F) -> GenericScalar<F> {
+        let mut out = GenericScalar::new_op(
+            self.join_data().powf(exponent),
+            vec![self],
+            Operation::Pow,
             "",
-        )
+        );
+        out.set_params(OpParams::Pow { exponent });
+
+        out
     }
-}
-//
-// impl<'a> ops::Add<&dyn Numeric> for &'a Scalar<'a> {
-//     type Output = Scalar<'a>;
-//     fn add(self, rhs: &dyn Numeric) -> Self::Output {
-//         static rhs = Scalar::new(rhs.to_f32())
-//         Scalar::<'a>::new_full(
-//             self.join_data() + rhs.to_f32(),
-//             vec![self, &Scalar::new(rhs.to_f32(), "tmp")],
-//             0.0,
-//             Operation::Add,
-//             "",
-//         )
-//     }
-// }
-//
-impl<'a> ops::Sub for &'a Scalar<'a> {
-    type Output = Scalar<'a>;
-    fn sub(self, rhs: Self) -> Self::Output {
-        Scalar::<'a>::new_full(
-            self.join_data() - rhs.join_data(),
-            vec![self, rhs],
-            0.0,
-            Operation::Sub,
+
+    /// Raises this Scalar to a constant integer power, `n`. This is exact for small powers, where
+    /// `pow` may round, and is well-defined for negative Scalars. A power of 0 gives a constant 1,
+    /// with a gradient of 0 (even at 0), and a negative power behaves like division.
+    pub fn powi(&self, n: i32) -> GenericScalar<F> {
+        let mut out =
+            GenericScalar::new_op(self.join_data().powi(n), vec![self], Operation::Powi, "");
+        out.set_params(OpParams::Powi { exponent: n });
+
+        out
+    }
+
+    /// Raises this Scalar to the power of another Scalar, `exponent`. Unlike with `pow`, the exponent
+    /// receives a gradient too, of `a^b * ln(a)`. Since ln(a) is undefined for a ≤ 0, the exponent's
+    /// gradient is taken to be zero there instead (which is also the limit as a approaches 0 from
+    /// above, for positive b). The data itself follows `f32::powf`, so it is NaN whenever a is
+    /// negative and b is not an integer.
+    pub fn powf(&self, exponent: &Self) -> GenericScalar<F> {
+        GenericScalar::new_op(
+            self.join_data().powf(exponent.join_data()),
+            vec![self, exponent],
+            Operation::PowVar,
             "",
         )
     }
-}
 
-impl<'a> ops::Mul for &'a Scalar<'a> {
-    type Output = Scalar<'a>;
-    fn mul(self, rhs: Self) -> Self::Output {
-        Scalar::<'a>::new_full(
-            self.join_data() * rhs.join_data(),
-            vec![self, rhs],
-            0.0,
-            Operation::Mul,
+    /// Applies the binary operation `op` to this Scalar and the constant `rhs`, in that order. The
+    /// constant becomes a leaf child of the resulting node.
+    fn with_constant(&self, rhs: impl Numeric, op: Operation) -> GenericScalar<F> {
+        let (a, b) = (self.join_data(), rhs.to_float::<F>());
+        let data = match op {
+            Operation::Add => a + b,
+            Operation::Sub => a - b,
+            Operation::Mul => a * b,
+            Operation::Div => a / b,
+            _ => panic!("`{}` is not a binary operation!", op),
+        };
+
+        let constant = GenericScalar::constant(rhs);
+        GenericScalar::new_op(data, vec![self, &constant], op, "")
+    }
+
+    /// Computes e^x, where x is the data stored in this Scalar.
+    pub fn exp(&self) -> GenericScalar<F> {
+        GenericScalar::new_op(self.join_data().exp(), vec![self], Operation::Exp, "")
+    }
+
+    /// Applies the hyperbolic tangent to this Scalar, squashing its data into the range (-1, 1).
+    pub fn tanh(&self) -> GenericScalar<F> {
+        GenericScalar::new_op(self.join_data().tanh(), vec![self], Operation::Tanh, "")
+    }
+
+    /// Applies the rectified linear unit to this Scalar, i.e. max(0, x).
+    pub fn relu(&self) -> GenericScalar<F> {
+        GenericScalar::new_op(
+            self.join_data().max(F::zero()),
+            vec![self],
+            Operation::ReLU,
             "",
         )
     }
-}
 
-impl<'a> ops::Div for &'a Scalar<'a> {
-    type Output = Scalar<'a>;
-    fn div(self, rhs: Self) -> Self::Output {
-        Scalar::<'a>::new_full(
-            self.join_data() / rhs.join_data(),
-            vec![self, rhs],
-            0.0,
-            Operation::Div,
+    /// Applies the logistic sigmoid to this Scalar, i.e. 1 / (1 + e^-x).
+    pub fn sigmoid(&self) -> GenericScalar<F> {
+        GenericScalar::new_op(
+            sigmoid(self.join_data()),
+            vec![self],
+            Operation::Sigmoid,
             "",
         )
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Takes the square root of this Scalar. Like `f32::sqrt`, the data is NaN when this Scalar is
+    /// negative, and the NaN then propagates to the gradient during `backward`. At exactly 0, the
+    /// data is 0 but the gradient is infinite, since √x is not differentiable there.
+    pub fn sqrt(&self) -> GenericScalar<F> {
+        GenericScalar::new_op(self.join_data().sqrt(), vec![self], Operation::Sqrt, "")
+    }
 
-    mod basic_ops {
-        use super::*;
-        #[test]
-        fn test_add() {
-            let s1 = &Scalar::new(3.2f32, "s1");
-            let s2 = &Scalar::new(4.7, "s2");
+    /// Takes the absolute value of this Scalar. Its gradient is the sign of this Scalar (1 or -1)
+    /// times the upstream gradient, and since |x| is not differentiable at 0, the subgradient 0 is
+    /// used there, as with `relu`.
+    pub fn abs(&self) -> GenericScalar<F> {
+        GenericScalar::new_op(self.join_data().abs(), vec![self], Operation::Abs, "")
+    }
 
-            let result = Scalar::new_full(3.2 + 4.7, vec![s1, s2], 0.0, Operation::Add, "result");
+    /// Returns the larger of this Scalar and `other`. The whole upstream gradient is routed to
+    /// whichever Scalar was selected, and the other receives none. On a tie, this Scalar (the
+    /// first) is selected.
+    pub fn max(&self, other: &Self) -> GenericScalar<F> {
+        let (a, b) = (self.join_data(), other.join_data());
+        let data = if a >= b { a } else { b };
 
-            assert_eq!(s1 + s2, result);
-        }
+        GenericScalar::new_op(data, vec![self, other], Operation::Max, "")
+    }
 
-        #[test]
-        fn test_sub() {
-            let s1 = &Scalar::new(3.2, "s1");
-            let s2 = &Scalar::new(4.7, "s2");
+    /// Returns the smaller of this Scalar and `other`. As with `max`, the whole upstream gradient
+    /// is routed to whichever Scalar was selected, and on a tie, this Scalar (the first) is.
+    pub fn min(&self, other: &Self) -> GenericScalar<F> {
+        let (a, b) = (self.join_data(), other.join_data());
+        let data = if a <= b { a } else { b };
 
-            let result = Scalar::new_full(3.2 - 4.7, vec![s1, s2], 0.0, Operation::Sub, "result");
+        GenericScalar::new_op(data, vec![self, other], Operation::Min, "")
+    }
 
-            assert_eq!(s1 - s2, result);
-        }
+    /// Takes the sine of this Scalar, in radians.
+    pub fn sin(&self) -> GenericScalar<F> {
+        GenericScalar::new_op(self.join_data().sin(), vec![self], Operation::Sin, "")
+    }
 
-        #[test]
-        fn test_mul() {
-            let s1 = &Scalar::new(3.2, "s1");
-            let s2 = &Scalar::new(4.7, "s2");
+    /// Takes the cosine of this Scalar, in radians.
+    pub fn cos(&self) -> GenericScalar<F> {
+        GenericScalar::new_op(self.join_data().cos(), vec![self], Operation::Cos, "")
+    }
 
-            let result = Scalar::new_full(3.2 * 4.7, vec![s1, s2], 0.0, Operation::Mul, "result");
+    /// Takes the reciprocal of this Scalar, `1 / x`. This is a single node, unlike dividing a
+    /// constant 1 by this Scalar. As with division, the data is infinite when this Scalar is 0, and
+    /// its gradient is then negative infinity.
+    pub fn recip(&self) -> GenericScalar<F> {
+        GenericScalar::new_op(self.join_data().recip(), vec![self], Operation::Recip, "")
+    }
 
-            assert_eq!(s1 * s2, result);
-        }
+    /// Takes the hyperbolic sine of this Scalar.
+    pub fn sinh(&self) -> GenericScalar<F> {
+        GenericScalar::new_op(self.join_data().sinh(), vec![self], Operation::Sinh, "")
+    }
 
-        #[test]
-        fn test_div() {
-            let s1 = &Scalar::new(3.2, "s1");
-            let s2 = &Scalar::new(4.7, "s2");
+    /// Takes the hyperbolic cosine of this Scalar.
+    pub fn cosh(&self) -> GenericScalar<F> {
+        GenericScalar::new_op(self.join_data().cosh(), vec![self], Operation::Cosh, "")
+    }
 
-            let result = Scalar::new_full(3.2 / 4.7, vec![s1, s2], 0.0, Operation::Div, "result");
+    /// Takes the natural logarithm of this Scalar. Like `f32::ln`, the data is NaN when this Scalar
+    /// is negative, and negative infinity at exactly 0 (where the gradient is infinite).
+    pub fn ln(&self) -> GenericScalar<F> {
+        GenericScalar::new_op(self.join_data().ln(), vec![self], Operation::Ln, "")
+    }
 
-            assert_eq!(s1 / s2, result);
+    /// Returns `out` if its data is finite, and otherwise the error describing why it is not.
+    fn check_finite(out: GenericScalar<F>) -> Result<GenericScalar<F>, ArithError> {
+        let data = out.join_data();
+        if data.is_nan() {
+            Err(ArithError::NaN(out.inner._op))
+        } else if data.is_infinite() {
+            Err(ArithError::Infinite(out.inner._op))
+        } else {
+            Ok(out)
         }
+    }
 
-        #[test]
-        fn test_joins() {
-            let s1 = Scalar::join_f32(1, 23, 3, 2);
-            assert_eq!(s1, 23.03);
+    /// Adds `other` to this Scalar like `+`, but returns an error if the sum is NaN or infinite
+    /// (for instance, because it overflowed), instead of a Scalar holding it.
+    pub fn try_add(&self, other: &Self) -> Result<GenericScalar<F>, ArithError> {
+        GenericScalar::check_finite(self + other)
+    }
 
-            let s2 = Scalar::join_f32(-1, 23, 3, 2);
-            assert_eq!(s2, -23.03);
-        }
+    /// Subtracts `other` from this Scalar like `-`, but returns an error if the difference is NaN or
+    /// infinite.
+    pub fn try_sub(&self, other: &Self) -> Result<GenericScalar<F>, ArithError> {
+        GenericScalar::check_finite(self - other)
+    }
 
-        #[test]
-        fn test_partial_eq() {
-            let s1 = Scalar::new(3.2, "s1");
-            let s2 = Scalar::new(3.2, "s2");
-            let s3 = Scalar::new(3.2, "s3");
-            let s4 = Scalar::new(3.2, "s4");
-            let v = vec![s1.clone(), s2.clone(), s3.clone()];
+    /// Multiplies this Scalar by `other` like `*`, but returns an error if the product is NaN or
+    /// infinite.
+    pub fn try_mul(&self, other: &Self) -> Result<GenericScalar<F>, ArithError> {
+        GenericScalar::check_finite(self * other)
+    }
 
-            assert!(v.contains(&s4));
-        }
+    /// Divides this Scalar by `other` like `/`, but returns an error if the quotient is NaN or
+    /// infinite, as it is when dividing by zero.
+    ///
+    /// ```
+    /// use minigrad::{ArithError, Operation, Scalar};
+    ///
+    /// let a = Scalar::new(1.0, "a");
+    /// let zero = Scalar::new(0.0, "zero");
+    /// assert_eq!(a.try_div(&zero), Err(ArithError::Infinite(Operation::Div)));
+    /// ```
+    pub fn try_div(&self, other: &Self) -> Result<GenericScalar<F>, ArithError> {
+        GenericScalar::check_finite(self / other)
     }
 
-    mod backward {
-        use float_cmp::approx_eq;
+    /// Like `backward`, but seeds this Scalar's gradient with `seed` rather than 1, so that every
+    /// gradient in the graph is scaled by `seed`. This is the single-output case of a
+    /// vector-Jacobian product, like passing `gradient` to `backward` in PyTorch.
+    ///
+    /// ```
+    /// use minigrad::Scalar;
+    ///
+    /// let a = Scalar::new(3.0, "a");
+    /// let b = &a * &a;
+    /// b.backward_with(0.5);
+    /// assert_eq!(a.grad(), 3.0);
+    /// ```
+    pub fn backward_with(&self, seed: F) {
+        self.zero_grad_all();
+        self.backpropagate(seed);
+    }
 
-        use super::*;
+    /// Like `backward`, but only lets gradients flow through this Scalar and the Scalars in
+    /// `subset`, which are found by identity. Every other Scalar in the graph is left with a zero
+    /// gradient and passes nothing on to its children, so only the paths made up entirely of
+    /// Scalars in `subset` contribute. This is meant for debugging, to find out which part of a
+    /// graph a gradient comes from.
+    ///
+    /// ```
+    /// use minigrad::Scalar;
+    ///
+    /// let a = Scalar::new(2.0, "a");
+    /// let left = &a * 3.0;
+    /// let right = a.exp();
+    /// let y = &left + &right;
+    ///
+    /// // Only the gradient flowing through `left` reaches `a`.
+    /// y.backward_through(&[&left, &a]);
+    /// assert_eq!(a.grad(), 3.0);
+    /// assert_eq!(right.grad(), 0.0);
+    /// ```
+    pub fn backward_through(&self, subset: &[&Self]) {
+        let allowed: HashSet<*const ScalarNode<F>> = subset
+            .iter()
+            .map(|node| Arc::as_ptr(&node.inner))
+            .chain(std::iter::once(Arc::as_ptr(&self.inner)))
+            .collect();
 
-        #[test]
-        fn test_add_backward() {
-            let a = &Scalar::new(3.1, "a");
-            let b = &Scalar::new(4.2, "b");
-            let mut c = a + b;
+        self.zero_grad_all();
+        self.set_grad(F::one());
 
-            c.backward();
+        for node in parse_topology(self, true) {
+            if allowed.contains(&Arc::as_ptr(&node.inner)) {
+                node.derive();
+            }
+        }
 
-            assert_eq!(c.join_grad(), 1.0);
-            assert_eq!(b.join_grad(), 1.0);
-            assert_eq!(a.join_grad(), 1.0);
+        // Scalars outside the subset may still have received a gradient from a parent inside it.
+        // This includes constants, which the pruned walk above never reaches.
+        for node in parse_topology(self, false) {
+            if !allowed.contains(&Arc::as_ptr(&node.inner)) {
+                node.zero_grad();
+            }
         }
+    }
 
-        #[test]
-        fn test_sub_backward() {
-            let a = &Scalar::new(3.1, "a");
+    // Seeds this Scalar's gradient with `seed` and propagates it through the graph, adding onto
+    // the gradients already held by its leaves. This is the body of `backward_accumulate`.
+    fn backpropagate(&self, seed: F) {
+        // Subgraphs made only of constants need no gradients, so they are skipped entirely. A
+        // constant still receives the gradient from its parent, but it goes no further.
+        let topology = parse_topology(self, true);
+
+        // Only leaves keep their gradients across passes. A Scalar computed from others has
+        // already passed the gradient it held on to its children, so keeping it would pass it on
+        // a second time, counting the earlier pass twice. A Scalar computed only from constants is
+        // pruned from the topology, but still receives a gradient from its parent, so it is
+        // cleared here too.
+        for node in &topology {
+            if !node.inner._children.is_empty() {
+                node.zero_grad();
+            }
+            for child in &node.inner._children {
+                if !child.requires_grad() && !child.inner._children.is_empty() {
+                    child.zero_grad();
+                }
+            }
+        }
+        self.set_grad(seed);
+
+        #[cfg(feature = "log")]
+        log::trace!("Backpropagating through {} nodes", topology.len());
+
+        // Each node in the topology is a handle to the actual node in the graph (not a copy), so
+        // gradients written into its children are visible to every other handle sharing them.
+        for node in topology {
+            node.derive();
+            #[cfg(feature = "log")]
+            log::trace!("Derived {:?}", node);
+        }
+    }
+
+    // Recomputes the data of this Scalar from the current data of its children, exactly as the
+    // operation that created it did. A leaf has nothing to recompute from, so it keeps its data.
+    fn evaluate(&self) -> F {
+        let data: Vec<F> = self.inner._children.iter().map(|c| c.join_data()).collect();
+        match self.inner._op {
+            Operation::Add => data[0] + data[1],
+            Operation::Sub => data[0] - data[1],
+            Operation::Mul => data[0] * data[1],
+            Operation::Div => data[0] / data[1],
+            Operation::Pow => data[0].powf(self.pow_exponent()),
+            Operation::Powi => data[0].powi(self.powi_exponent()),
+            Operation::PowVar => data[0].powf(data[1]),
+            Operation::Exp => data[0].exp(),
+            Operation::Tanh => data[0].tanh(),
+            Operation::ReLU => data[0].max(F::zero()),
+            Operation::Sigmoid => sigmoid(data[0]),
+            Operation::Neg => -data[0],
+            Operation::Sqrt => data[0].sqrt(),
+            Operation::Abs => data[0].abs(),
+            Operation::Max => {
+                if data[0] >= data[1] {
+                    data[0]
+                } else {
+                    data[1]
+                }
+            }
+            Operation::Min => {
+                if data[0] <= data[1] {
+                    data[0]
+                } else {
+                    data[1]
+                }
+            }
+            Operation::Sin => data[0].sin(),
+            Operation::Cos => data[0].cos(),
+            Operation::Recip => data[0].recip(),
+            Operation::Sinh => data[0].sinh(),
+            Operation::Cosh => data[0].cosh(),
+            Operation::Ln => data[0].ln(),
+            Operation::Sum => data.iter().fold(F::zero(), |total, &x| total + x),
+            Operation::Mean if data.is_empty() => F::zero(),
+            Operation::Mean => {
+                data.iter().fold(F::zero(), |total, &x| total + x) / F::cast(data.len())
+            }
+            Operation::Base => self.join_data(),
+        }
+    }
+}
+
+impl<F: Float> Derivable for GenericScalar<F> {
+    fn derive(&self) {
+        let _parent_grad = self.join_grad();
+
+        if self.inner._children.is_empty() {
+            return;
+        }
+
+        // Every arm adds its contribution onto each child's gradient with `accumulate_grad`, which
+        // reads the child's gradient at the moment it is updated. Both children of a binary
+        // operation may be the same node (as in `&a * &a`), in which case both contributions
+        // accumulate onto that single node.
+        let children = &self.inner._children;
+        match self.inner._op {
+            // For each of the following operations, let z be the final output value produced by
+            // the overall computation, a (and b, if applicable) be the current child nodes being
+            // processed, and y be the output of applying the found operation to those nodes.
+            Operation::Add => {
+                // Here, we have y = a + b. The following holds:
+                //  1. ∂y/∂a = 1.0, and therefore, ∂z/∂a = ∂z/∂y
+                children[0].accumulate_grad(_parent_grad);
+                //  2. ∂y/∂b = 1.0, and therefore, ∂z/∂b = ∂z/∂y
+                children[1].accumulate_grad(_parent_grad);
+            }
+            Operation::Sub => {
+                // Here, we have y = a - b. The following holds:
+                //  1. ∂y/∂a = 1.0, and therefore, ∂z/∂a = ∂z/∂y
+                children[0].accumulate_grad(_parent_grad);
+                //  2. ∂y/∂b = -1.0, and therefore, ∂z/∂b = -1.0 * ∂z/∂y
+                children[1].accumulate_grad(-_parent_grad);
+            }
+            Operation::Mul => {
+                // We will need access to `a` and `b` to calculate the derivatives, unlike the
+                // previous operations.
+                let orig_data0 = children[0].join_data(); // Represents `a` here
+                let orig_data1 = children[1].join_data(); // Represents `b` here
+
+                // Here, we have y = ab. The following holds:
+                //  1. ∂y/∂a = b, and therefore, ∂z/∂a = ∂z/∂y * b
+                children[0].accumulate_grad(_parent_grad * orig_data1);
+                //  2. ∂y/∂b = a, and therefore, ∂z/∂b = ∂z/∂y * a
+                children[1].accumulate_grad(_parent_grad * orig_data0);
+            }
+            Operation::Div => {
+                // We will need access to `a` and `b` here as well.
+                let orig_data0 = children[0].join_data();
+                let orig_data1 = children[1].join_data();
+
+                // Here, we have y = a ÷ b, or y = 1/b * a. The following holds:
+                //  1. ∂y/∂a = 1/b, and therefore, ∂z/∂a = ∂z/∂y * 1/b
+                children[0].accumulate_grad(_parent_grad / orig_data1);
+                //  2. ∂y/∂b = -a * b^-2, and therefore, ∂z/∂b = -∂z/∂y * (a/b^2)
+                children[1].accumulate_grad(-_parent_grad * orig_data0 / orig_data1.powi(2));
+            }
+            Operation::Pow => {
+                let orig_data0 = children[0].join_data();
+                let exponent = self.pow_exponent();
+
+                // Here, we have y = a^n for some constant n. The following holds:
+                //  1. ∂y/∂a = n * a^(n - 1), and therefore, ∂z/∂a = ∂z/∂y * n * a^(n - 1)
+                children[0].accumulate_grad(
+                    _parent_grad * exponent * orig_data0.powf(exponent - F::one()),
+                );
+            }
+            Operation::Exp => {
+                // Here, we have y = e^a. The following holds:
+                //  1. ∂y/∂a = e^a = y, and therefore, ∂z/∂a = ∂z/∂y * y. We can reuse this node's
+                //     own data rather than recomputing the exponential.
+                children[0].accumulate_grad(_parent_grad * self.join_data());
+            }
+            Operation::Tanh => {
+                // Here, we have y = tanh(a). The following holds:
+                //  1. ∂y/∂a = 1 - tanh^2(a) = 1 - y^2, and therefore, ∂z/∂a = ∂z/∂y * (1 - y^2)
+                let orig_out = self.join_data();
+                children[0].accumulate_grad(_parent_grad * (F::one() - orig_out.powi(2)));
+            }
+            Operation::ReLU => {
+                let orig_data0 = children[0].join_data();
+
+                // Here, we have y = max(0, a). The following holds:
+                //  1. ∂y/∂a = 1 when a > 0 and 0 when a < 0, and therefore, ∂z/∂a is either ∂z/∂y or
+                //     0. ReLU is not differentiable at a = 0, so we pick the subgradient 0 there.
+                if orig_data0 > F::zero() {
+                    children[0].accumulate_grad(_parent_grad);
+                }
+            }
+            Operation::Sigmoid => {
+                // Here, we have y = σ(a). The following holds:
+                //  1. ∂y/∂a = σ(a) * (1 - σ(a)) = y * (1 - y), and therefore,
+                //     ∂z/∂a = ∂z/∂y * y * (1 - y)
+                let orig_out = self.join_data();
+                children[0].accumulate_grad(_parent_grad * orig_out * (F::one() - orig_out));
+            }
+            Operation::Neg => {
+                // Here, we have y = -a. The following holds:
+                //  1. ∂y/∂a = -1.0, and therefore, ∂z/∂a = -1.0 * ∂z/∂y
+                children[0].accumulate_grad(-_parent_grad);
+            }
+            Operation::Sqrt => {
+                // Here, we have y = √a. The following holds:
+                //  1. ∂y/∂a = 1 / (2√a) = 1 / 2y, and therefore, ∂z/∂a = ∂z/∂y / 2y
+                let orig_out = self.join_data();
+                children[0].accumulate_grad(_parent_grad / (F::cast(2) * orig_out));
+            }
+            Operation::Sum => {
+                // Here, we have y = a + b + ... The following holds for every child c:
+                //  1. ∂y/∂c = 1.0, and therefore, ∂z/∂c = ∂z/∂y
+                for child in children.iter() {
+                    child.accumulate_grad(_parent_grad);
+                }
+            }
+            Operation::Mean => {
+                // Here, we have y = (a + b + ...) / n. The following holds for every child c:
+                //  1. ∂y/∂c = 1/n, and therefore, ∂z/∂c = ∂z/∂y / n
+                let count = F::cast(children.len());
+                for child in children.iter() {
+                    child.accumulate_grad(_parent_grad / count);
+                }
+            }
+            Operation::PowVar => {
+                let orig_data0 = children[0].join_data();
+                let orig_data1 = children[1].join_data();
+                let orig_out = self.join_data();
+
+                // Here, we have y = a^b. The following holds:
+                //  1. ∂y/∂a = b * a^(b - 1), and therefore, ∂z/∂a = ∂z/∂y * b * a^(b - 1)
+                children[0].accumulate_grad(
+                    _parent_grad * orig_data1 * orig_data0.powf(orig_data1 - F::one()),
+                );
+                //  2. ∂y/∂b = a^b * ln(a) = y * ln(a), and therefore, ∂z/∂b = ∂z/∂y * y * ln(a).
+                //     ln(a) is undefined for a ≤ 0, in which case ∂y/∂b is taken to be zero.
+                if orig_data0 > F::zero() {
+                    children[1].accumulate_grad(_parent_grad * orig_out * orig_data0.ln());
+                }
+            }
+            Operation::Abs => {
+                let orig_data0 = children[0].join_data();
+
+                // Here, we have y = |a|. The following holds:
+                //  1. ∂y/∂a = sign(a) (taken to be 0 at a = 0), and therefore,
+                //     ∂z/∂a = ∂z/∂y * sign(a)
+                if orig_data0 != F::zero() {
+                    children[0].accumulate_grad(_parent_grad * orig_data0.signum());
+                }
+            }
+            Operation::Max | Operation::Min => {
+                let orig_data0 = children[0].join_data();
+                let orig_data1 = children[1].join_data();
+
+                // Here, we have y = max(a, b) or y = min(a, b). The following holds:
+                //  1. ∂y/∂c = 1.0 for whichever child c was selected (the first, on a tie), and
+                //     0.0 for the other, and therefore, ∂z/∂c = ∂z/∂y for only the selected child
+                let first_selected = match self.inner._op {
+                    Operation::Max => orig_data0 >= orig_data1,
+                    _ => orig_data0 <= orig_data1,
+                };
+                children[if first_selected { 0 } else { 1 }].accumulate_grad(_parent_grad);
+            }
+            Operation::Sin => {
+                let orig_data0 = children[0].join_data();
+
+                // Here, we have y = sin(a). The following holds:
+                //  1. ∂y/∂a = cos(a), and therefore, ∂z/∂a = ∂z/∂y * cos(a)
+                children[0].accumulate_grad(_parent_grad * orig_data0.cos());
+            }
+            Operation::Cos => {
+                let orig_data0 = children[0].join_data();
+
+                // Here, we have y = cos(a). The following holds:
+                //  1. ∂y/∂a = -sin(a), and therefore, ∂z/∂a = -∂z/∂y * sin(a)
+                children[0].accumulate_grad(-_parent_grad * orig_data0.sin());
+            }
+            Operation::Powi => {
+                let orig_data0 = children[0].join_data();
+                let n = self.powi_exponent();
+
+                // Here, we have y = a^n for some constant integer n. The following holds:
+                //  1. ∂y/∂a = n * a^(n - 1), and therefore, ∂z/∂a = ∂z/∂y * n * a^(n - 1). When n
+                //     is 0, y is constant, so ∂y/∂a is 0 (even at a = 0, where a^-1 is undefined).
+                if n != 0 {
+                    children[0].accumulate_grad(_parent_grad * F::cast(n) * orig_data0.powi(n - 1));
+                }
+            }
+            Operation::Recip => {
+                // Here, we have y = 1 / a. The following holds:
+                //  1. ∂y/∂a = -1 / a^2 = -y^2, and therefore, ∂z/∂a = -y^2 * ∂z/∂y
+                let orig_out = self.join_data();
+                children[0].accumulate_grad(-orig_out * orig_out * _parent_grad);
+            }
+            Operation::Sinh => {
+                let orig_data0 = children[0].join_data();
+
+                // Here, we have y = sinh(a). The following holds:
+                //  1. ∂y/∂a = cosh(a), and therefore, ∂z/∂a = ∂z/∂y * cosh(a)
+                children[0].accumulate_grad(_parent_grad * orig_data0.cosh());
+            }
+            Operation::Cosh => {
+                let orig_data0 = children[0].join_data();
+
+                // Here, we have y = cosh(a). The following holds:
+                //  1. ∂y/∂a = sinh(a), and therefore, ∂z/∂a = ∂z/∂y * sinh(a)
+                children[0].accumulate_grad(_parent_grad * orig_data0.sinh());
+            }
+            Operation::Ln => {
+                let orig_data0 = children[0].join_data();
+
+                // Here, we have y = ln(a). The following holds:
+                //  1. ∂y/∂a = 1 / a, and therefore, ∂z/∂a = ∂z/∂y / a
+                children[0].accumulate_grad(_parent_grad / orig_data0);
+            }
+            // The Base operation is just the default for leaf nodes, so there is nothing to do here
+            // (leaf nodes have no children). There is deliberately no wildcard arm, so that a new
+            // operation cannot be added without giving it a derivative.
+            Operation::Base => (),
+        }
+    }
+
+    /// Given a Scalar, takes its derivative and the derivative of all its children (direct or
+    /// indirect), until every node in the Scalar's graph holds its gradient. Gradients from any
+    /// earlier backward pass are cleared first.
+    fn backward(&self) {
+        self.zero_grad_all();
+        self.backward_accumulate();
+    }
+
+    /// Like `backward`, but adds onto the gradients already held by the graph.
+    fn backward_accumulate(&self) {
+        self.backpropagate(F::one());
+    }
+}
+
+/// Computes the gradients of several outputs at once, each scaled by its weight, so that every
+/// Scalar ends up with the gradient of the weighted sum of the outputs (for instance, a loss plus
+/// a scaled regularizer). Like `backward`, any gradients left in the graphs by an earlier pass are
+/// cleared first. All of the outputs are derived in a single pass, so outputs may share inputs,
+/// and one output may even be computed from another.
+pub fn batch_backward<F: Float>(outputs: &[(&GenericScalar<F>, F)]) {
+    let roots: Vec<&GenericScalar<F>> = outputs.iter().map(|(output, _)| *output).collect();
+    for node in parse_topology_all(&roots, false) {
+        node.zero_grad();
+    }
+
+    // Accumulated rather than set, in case the same output is listed more than once.
+    for (output, weight) in outputs {
+        output.accumulate_grad(*weight);
+    }
+
+    let topology = parse_topology_all(&roots, true);
+
+    #[cfg(feature = "log")]
+    log::trace!(
+        "Backpropagating {} outputs through {} nodes",
+        outputs.len(),
+        topology.len()
+    );
+
+    for node in topology {
+        node.derive();
+        #[cfg(feature = "log")]
+        log::trace!("Derived {:?}", node);
+    }
+}
+
+/// Computes the Jacobian of `outputs` with respect to `inputs`: row `i` holds the gradient of
+/// `outputs[i]` with respect to each of the inputs, in order. Each row is computed with its own
+/// `backward` pass, seeded with 1 on that output alone, and the inputs' gradients are cleared
+/// between rows, so an input that an output does not depend on gets a zero. Afterwards, the graphs
+/// hold the gradients of the last output.
+///
+/// ```
+/// use minigrad::{jacobian, Scalar};
+///
+/// let x = Scalar::new(2.0, "x");
+/// let y = Scalar::new(3.0, "y");
+/// let rows = jacobian(&[&(&x * &y), &x.exp()], &[&x, &y]);
+/// assert_eq!(rows, vec![vec![3.0, 2.0], vec![2f32.exp(), 0.0]]);
+/// ```
+pub fn jacobian<F: Float>(
+    outputs: &[&GenericScalar<F>],
+    inputs: &[&GenericScalar<F>],
+) -> Vec<Vec<F>> {
+    outputs
+        .iter()
+        .map(|output| {
+            for input in inputs {
+                input.zero_grad();
+            }
+            output.backward();
+
+            inputs.iter().map(|input| input.join_grad()).collect()
+        })
+        .collect()
+}
+
+/// Orders the graph rooted at `node` so that every node comes before all of its children (a
+/// depth-first post-order, reversed). This is the order in which `backward` must derive nodes,
+/// since a node's gradient is only complete once every node that uses it has been derived.
+///
+/// If `prune` is set, the search does not descend into children that do not require gradients,
+/// since nothing below them could need one. Such children are left out of the topology entirely.
+fn parse_topology<F: Float>(node: &GenericScalar<F>, prune: bool) -> Vec<&GenericScalar<F>> {
+    parse_topology_all(&[node], prune)
+}
+
+/// Like `parse_topology`, but orders the graphs rooted at each of `roots` together, so that a node
+/// shared between them (or a root used by another root) still comes before all of its children.
+fn parse_topology_all<'a, F: Float>(
+    roots: &[&'a GenericScalar<F>],
+    prune: bool,
+) -> Vec<&'a GenericScalar<F>> {
+    let mut topology: Vec<&GenericScalar<F>> = vec![];
+    // Nodes are tracked by address rather than by value, which is also far cheaper to look up.
+    let mut visited: HashSet<*const ScalarNode<F>> = HashSet::new();
+
+    // The depth-first search keeps its own stack rather than recursing, so that deep graphs (such
+    // as a long chain of additions) cannot overflow the call stack. Each entry is a node, along
+    // with whether its children have already been pushed above it.
+    let mut stack: Vec<(&GenericScalar<F>, bool)> =
+        roots.iter().rev().map(|root| (*root, false)).collect();
+    while let Some((v, expanded)) = stack.pop() {
+        if expanded {
+            // Only pushed once all of its children have been, so that reversing the topology puts
+            // every node before its children.
+            topology.push(v);
+            continue;
+        }
+
+        // A node may appear as more than one child of the same parent (as in `&a * &a`). It is
+        // only added to the topology once, since `derive` on the parent already accumulates both
+        // contributions onto it; adding it twice would make it propagate its gradient twice.
+        if visited.insert(Arc::as_ptr(&v.inner)) {
+            stack.push((v, true));
+            // Pushed in reverse, so that the first child is searched first.
+            for child in v.inner._children.iter().rev() {
+                if !prune || child.requires_grad() {
+                    stack.push((child, false));
+                }
+            }
+        }
+    }
+    topology.reverse();
+
+    topology
+}
+
+impl<F: Float> ops::Add for &GenericScalar<F> {
+    type Output = GenericScalar<F>;
+    fn add(self, rhs: Self) -> Self::Output {
+        GenericScalar::<F>::new_op(
+            self.join_data() + rhs.join_data(),
+            vec![self, rhs],
+            Operation::Add,
+            "",
+        )
+    }
+}
+
+// Operations between a Scalar and a raw numeric constant. The constant is wrapped in a constant
+// leaf Scalar (see `GenericScalar::constant`), and the resulting node keeps the only `Arc` handle
+// to it, so it lives exactly as long as the node that uses it. It still receives a gradient from
+// that node during `backward`, which can be read through `children()`, but passes nothing further.
+impl<F: Float, N: Numeric> ops::Add<N> for &GenericScalar<F> {
+    type Output = GenericScalar<F>;
+    fn add(self, rhs: N) -> Self::Output {
+        self.with_constant(rhs, Operation::Add)
+    }
+}
+
+impl<F: Float, N: Numeric> ops::Sub<N> for &GenericScalar<F> {
+    type Output = GenericScalar<F>;
+    fn sub(self, rhs: N) -> Self::Output {
+        self.with_constant(rhs, Operation::Sub)
+    }
+}
+
+impl<F: Float, N: Numeric> ops::Mul<N> for &GenericScalar<F> {
+    type Output = GenericScalar<F>;
+    fn mul(self, rhs: N) -> Self::Output {
+        self.with_constant(rhs, Operation::Mul)
+    }
+}
+
+impl<F: Float, N: Numeric> ops::Div<N> for &GenericScalar<F> {
+    type Output = GenericScalar<F>;
+    fn div(self, rhs: N) -> Self::Output {
+        self.with_constant(rhs, Operation::Div)
+    }
+}
+
+// Implements the four basic operators with a constant of type `$t` on the left of a Scalar
+// storing `$f`, as in `2.0 + &a`. The constant becomes the first child, which keeps the gradients
+// of `-` and `/` the right way around.
+macro_rules! impl_constant_lhs {
+    ($f:ty; $($t:ty),*) => {$(
+        impl ops::Add<&GenericScalar<$f>> for $t {
+            type Output = GenericScalar<$f>;
+            fn add(self, rhs: &GenericScalar<$f>) -> Self::Output {
+                &GenericScalar::constant(self) + rhs
+            }
+        }
+
+        impl ops::Sub<&GenericScalar<$f>> for $t {
+            type Output = GenericScalar<$f>;
+            fn sub(self, rhs: &GenericScalar<$f>) -> Self::Output {
+                &GenericScalar::constant(self) - rhs
+            }
+        }
+
+        impl ops::Mul<&GenericScalar<$f>> for $t {
+            type Output = GenericScalar<$f>;
+            fn mul(self, rhs: &GenericScalar<$f>) -> Self::Output {
+                &GenericScalar::constant(self) * rhs
+            }
+        }
+
+        impl ops::Div<&GenericScalar<$f>> for $t {
+            type Output = GenericScalar<$f>;
+            fn div(self, rhs: &GenericScalar<$f>) -> Self::Output {
+                &GenericScalar::constant(self) / rhs
+            }
+        }
+    )*};
+}
+
+// Each float type is only implemented for the Scalar storing it, so that an unsuffixed literal
+// such as `2.0` is inferred from the Scalar it is used with. Integers work with either, but need a
+// suffix (as in `2i32 / &a`), since an unsuffixed integer literal could be any of them.
+impl_constant_lhs!(f32; f32, i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+impl_constant_lhs!(f64; f64, i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+impl<F: Float> ops::Sub for &GenericScalar<F> {
+    type Output = GenericScalar<F>;
+    fn sub(self, rhs: Self) -> Self::Output {
+        GenericScalar::<F>::new_op(
+            self.join_data() - rhs.join_data(),
+            vec![self, rhs],
+            Operation::Sub,
+            "",
+        )
+    }
+}
+
+impl<F: Float> ops::Mul for &GenericScalar<F> {
+    type Output = GenericScalar<F>;
+    fn mul(self, rhs: Self) -> Self::Output {
+        GenericScalar::<F>::new_op(
+            self.join_data() * rhs.join_data(),
+            vec![self, rhs],
+            Operation::Mul,
+            "",
+        )
+    }
+}
+
+/// Divides one Scalar by another. Dividing by a Scalar holding zero never panics: the data follows
+/// IEEE 754, so `x / 0` is ±inf for nonzero `x` and `0 / 0` is NaN. During `backward`, the gradient
+/// of both the numerator and the denominator is likewise ±inf (or NaN for `0 / 0`), rather than a
+/// panic partway through the graph.
+impl<F: Float> ops::Div for &GenericScalar<F> {
+    type Output = GenericScalar<F>;
+    fn div(self, rhs: Self) -> Self::Output {
+        GenericScalar::<F>::new_op(
+            self.join_data() / rhs.join_data(),
+            vec![self, rhs],
+            Operation::Div,
+            "",
+        )
+    }
+}
+
+impl<F: Float> ops::Neg for &GenericScalar<F> {
+    type Output = GenericScalar<F>;
+    fn neg(self) -> Self::Output {
+        GenericScalar::<F>::new_op(-self.join_data(), vec![self], Operation::Neg, "")
+    }
+}
+
+// Implements each binary operator for owned Scalars (on either or both sides, as in `a + b` and
+// `a + &b`) and for an owned Scalar with a constant, by forwarding to the implementation for
+// references. A node only holds handles to its children, so the operands can be moved in and
+// dropped afterwards without affecting the graph.
+macro_rules! impl_owned_ops {
+    ($($trait:ident, $method:ident);*) => {$(
+        impl<F: Float> ops::$trait for GenericScalar<F> {
+            type Output = GenericScalar<F>;
+            fn $method(self, rhs: Self) -> Self::Output {
+                ops::$trait::$method(&self, &rhs)
+            }
+        }
+
+        impl<F: Float> ops::$trait<&GenericScalar<F>> for GenericScalar<F> {
+            type Output = GenericScalar<F>;
+            fn $method(self, rhs: &GenericScalar<F>) -> Self::Output {
+                ops::$trait::$method(&self, rhs)
+            }
+        }
+
+        impl<F: Float> ops::$trait<GenericScalar<F>> for &GenericScalar<F> {
+            type Output = GenericScalar<F>;
+            fn $method(self, rhs: GenericScalar<F>) -> Self::Output {
+                ops::$trait::$method(self, &rhs)
+            }
+        }
+
+        impl<F: Float, N: Numeric> ops::$trait<N> for GenericScalar<F> {
+            type Output = GenericScalar<F>;
+            fn $method(self, rhs: N) -> Self::Output {
+                ops::$trait::$method(&self, rhs)
+            }
+        }
+    )*};
+}
+
+impl_owned_ops!(Add, add; Sub, sub; Mul, mul; Div, div);
+
+impl<F: Float> ops::Neg for GenericScalar<F> {
+    type Output = GenericScalar<F>;
+    fn neg(self) -> Self::Output {
+        -&self
+    }
+}
+
+/// Computes the logistic sigmoid of `x`, 1 / (1 + e^-x).
+fn sigmoid<F: Float>(x: F) -> F {
+    // For very negative x, e^-x overflows to infinity, so the equivalent form e^x / (1 + e^x) is
+    // used instead (which only ever exponentiates a non-positive number).
+    if x >= F::zero() {
+        F::one() / (F::one() + (-x).exp())
+    } else {
+        x.exp() / (F::one() + x.exp())
+    }
+}
+
+/// How far apart two floats may be while still being considered equal by
+/// `assert_float_eq_within`.
+#[derive(Debug, Clone, Copy)]
+enum Tolerance {
+    /// At most this many representable values apart, which scales with the size of the floats.
+    Ulps(u32),
+    /// At most this far apart in absolute terms, which suits results expected to be near zero.
+    Epsilon(f32),
+}
+
+/// The tolerance for results that pass through transcendental operations (such as `exp` and `ln`),
+/// each of which can add a few ulps of rounding error.
+const TRANSCENDENTAL: Tolerance = Tolerance::Ulps(16);
+
+/// Given two floats `a` and `b`, asserts that the two floats are equal to each other, taking
+/// into account precision and rounding errors that might change some of the later decimal
+/// points of either float.
+#[track_caller]
+fn assert_float_eq(a: f32, b: f32) {
+    assert_float_eq_within(a, b, Tolerance::Ulps(4));
+}
+
+/// Like `assert_float_eq`, but with a given tolerance, for results that accumulate more rounding
+/// error.
+#[track_caller]
+fn assert_float_eq_within(a: f32, b: f32, tolerance: Tolerance) {
+    let equal = match tolerance {
+        Tolerance::Ulps(ulps) => approx_eq!(f32, a, b, ulps = ulps as i32),
+        Tolerance::Epsilon(epsilon) => approx_eq!(f32, a, b, epsilon = epsilon, ulps = 0),
+    };
+    assert!(
+        equal,
+        "{} and {} are not equal within {:?}",
+        a, b, tolerance
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod basic_ops {
+        use super::*;
+        #[test]
+        fn test_add() {
+            let s1 = &Scalar::new(3.2f32, "s1");
+            let s2 = &Scalar::new(4.7, "s2");
+
+            let result = Scalar::new_op(3.2 + 4.7, vec![s1, s2], Operation::Add, "result");
+
+            assert_eq!(s1 + s2, result);
+        }
+
+        #[test]
+        fn test_sub() {
+            let s1 = &Scalar::new(3.2, "s1");
+            let s2 = &Scalar::new(4.7, "s2");
+
+            let result = Scalar::new_op(3.2 - 4.7, vec![s1, s2], Operation::Sub, "result");
+
+            assert_eq!(s1 - s2, result);
+        }
+
+        #[test]
+        fn test_mul() {
+            let s1 = &Scalar::new(3.2, "s1");
+            let s2 = &Scalar::new(4.7, "s2");
+
+            let result = Scalar::new_op(3.2 * 4.7, vec![s1, s2], Operation::Mul, "result");
+
+            assert_eq!(s1 * s2, result);
+        }
+
+        #[test]
+        fn test_div() {
+            let s1 = &Scalar::new(3.2, "s1");
+            let s2 = &Scalar::new(4.7, "s2");
+
+            let result = Scalar::new_op(3.2 / 4.7, vec![s1, s2], Operation::Div, "result");
+
+            assert_eq!(s1 / s2, result);
+        }
+
+        #[test]
+        fn test_pow() {
+            let s1 = &Scalar::new(3.2, "s1");
+
+            let mut result = Scalar::new_op(3.2f32.powf(3.0), vec![s1], Operation::Pow, "result");
+            result.set_params(OpParams::Pow { exponent: 3.0 });
+
+            assert_eq!(s1.pow(3.0), result);
+        }
+
+        #[test]
+        fn test_exp() {
+            let s1 = &Scalar::new(3.2, "s1");
+
+            let result = Scalar::new_op(3.2f32.exp(), vec![s1], Operation::Exp, "result");
+
+            assert_eq!(s1.exp(), result);
+        }
+
+        #[test]
+        fn test_tanh() {
+            let s1 = &Scalar::new(0.7, "s1");
+
+            let result = Scalar::new_op(0.7f32.tanh(), vec![s1], Operation::Tanh, "result");
+
+            assert_eq!(s1.tanh(), result);
+        }
+
+        #[test]
+        fn test_relu() {
+            let s1 = &Scalar::new(3.2, "s1");
+            let s2 = &Scalar::new(-3.2, "s2");
+
+            let result1 = Scalar::new_op(3.2, vec![s1], Operation::ReLU, "result1");
+            let result2 = Scalar::new_op(0.0, vec![s2], Operation::ReLU, "result2");
+
+            assert_eq!(s1.relu(), result1);
+            assert_eq!(s2.relu(), result2);
+        }
+
+        #[test]
+        fn test_sigmoid() {
+            let s1 = &Scalar::new(0.0, "s1");
+            let s2 = &Scalar::new(-2.0, "s2");
+
+            assert_eq!(s1.sigmoid().join_data(), 0.5);
+            assert_float_eq(s2.sigmoid().join_data(), 1. / (1. + 2f32.exp()));
+        }
+
+        #[test]
+        fn test_neg() {
+            let s1 = &Scalar::new(3.2, "s1");
+
+            let result = Scalar::new_op(-3.2, vec![s1], Operation::Neg, "result");
+
+            assert_eq!(-s1, result);
+        }
+
+        #[test]
+        fn test_sqrt() {
+            let s1 = &Scalar::new(6.25, "s1");
+            let s2 = &Scalar::new(-1.0, "s2");
+
+            let result = Scalar::new_op(2.5, vec![s1], Operation::Sqrt, "result");
+
+            assert_eq!(s1.sqrt(), result);
+            assert!(s2.sqrt().data().is_nan());
+        }
+
+        #[test]
+        fn test_sum() {
+            let s1 = &Scalar::new(1.0, "s1");
+            let s2 = &Scalar::new(2.0, "s2");
+            let s3 = &Scalar::new(3.5, "s3");
+
+            let result = Scalar::new_op(6.5, vec![s1, s2, s3], Operation::Sum, "result");
+
+            assert_eq!(crate::sum(&[s1, s2, s3]), result);
+            assert_eq!(crate::sum::<f32>(&[]).data(), 0.0);
+        }
+
+        #[test]
+        fn test_mean() {
+            let s1 = &Scalar::new(1.0, "s1");
+            let s2 = &Scalar::new(2.0, "s2");
+            let s3 = &Scalar::new(6.0, "s3");
+
+            let result = Scalar::new_op(3.0, vec![s1, s2, s3], Operation::Mean, "result");
+
+            assert_eq!(crate::mean(&[s1, s2, s3]), result);
+            assert_eq!(crate::mean::<f32>(&[]).data(), 0.0);
+        }
+
+        #[test]
+        fn test_to_dot() {
+            let a = Scalar::new(1.0, "a");
+            let b = Scalar::new(2.0, "b");
+            let c = &a + &b;
+
+            let dot = c.to_dot();
+
+            assert!(dot.starts_with("digraph {"));
+            assert!(dot.contains("{ a | data 1.0000 | grad 0.0000 }"));
+            assert!(dot.contains("{ b | data 2.0000 | grad 0.0000 }"));
+            assert!(dot.contains("[label = \"+\"]"));
+            assert_eq!(dot.matches(" -> ").count(), 3);
+        }
+
+        #[test]
+        fn test_labels() {
+            let a = Scalar::new(1.0, "a");
+            let b = Scalar::new(2.0, "b");
+            let mut c = (&a + &b).with_label("c");
+
+            assert!(format!("{:?}", c).starts_with("Scalar(label = c, data = 3"));
+
+            c.set_label("total");
+            assert!(format!("{:?}", c).starts_with("Scalar(label = total, data = 3"));
+        }
+
+        #[test]
+        fn test_powf() {
+            let s1 = &Scalar::new(2.0, "s1");
+            let s2 = &Scalar::new(3.0, "s2");
+
+            let result = Scalar::new_op(8.0, vec![s1, s2], Operation::PowVar, "result");
+
+            assert_eq!(s1.powf(s2), result);
+        }
+
+        #[test]
+        fn test_abs() {
+            let s1 = &Scalar::new(-3.5, "s1");
+
+            let result = Scalar::new_op(3.5, vec![s1], Operation::Abs, "result");
+
+            assert_eq!(s1.abs(), result);
+        }
+
+        #[test]
+        fn test_max_min() {
+            let s1 = &Scalar::new(3.0, "s1");
+            let s2 = &Scalar::new(-1.0, "s2");
+
+            let max = Scalar::new_op(3.0, vec![s1, s2], Operation::Max, "max");
+            let min = Scalar::new_op(-1.0, vec![s1, s2], Operation::Min, "min");
+
+            assert_eq!(s1.max(s2), max);
+            assert_eq!(s1.min(s2), min);
+        }
+
+        #[test]
+        fn test_approx_eq() {
+            let s1 = Scalar::new(1.5, "s1");
+            let s2 = Scalar::new(1.50, "s2");
+            let s3 = Scalar::new(1.5000001, "s3");
+            let s4 = &Scalar::new(0.1, "s4") + 0.2;
+            let s5 = Scalar::new(0.3, "s5");
+
+            assert!(s1.approx_eq(&s2, 0));
+            assert!(s1.approx_eq(&s3, 1));
+            assert!(!s1.approx_eq(&Scalar::new(1.6, ""), 4));
+
+            // Structurally, `s4` is a sum and `s5` is a leaf, but their data is close enough.
+            assert!(s4.approx_eq(&s5, 4));
+            assert_ne!(s4, s5);
+        }
+
+        #[test]
+        fn test_equivalent_literals() {
+            // Data is stored as a single float rather than decomposed into digits, so every literal
+            // spelling of the same value produces structurally equal Scalars.
+            assert_eq!(Scalar::new(1.5, ""), Scalar::new(1.50, ""));
+            assert_eq!(Scalar::new(1.5, ""), Scalar::new(15e-1, ""));
+            assert_eq!(Scalar::new(2, ""), Scalar::new(2.0, ""));
+            assert_eq!(Scalar::new(0.1, ""), Scalar::new(1e-1, ""));
+            assert_eq!(Scalar::new(-0.0, ""), Scalar::new(0.0, ""));
+        }
+
+        #[test]
+        fn test_display() {
+            let a = &Scalar::new(1.0, "a");
+            let b = &Scalar::new(2.0, "b");
+            let c = &Scalar::new(3.0, "c");
+
+            assert_eq!(format!("{}", a + b), "(a + b)");
+            assert_eq!(format!("{}", &(a + b) * c), "((a + b) * c)");
+            assert_eq!(format!("{}", &(a * 2) - 0.5), "((a * 2) - 0.5)");
+            assert_eq!(format!("{}", (a / b).tanh()), "tanh((a / b))");
+            assert_eq!(format!("{}", -&a.pow(2.0)), "(-(a ^ 2))");
+            assert_eq!(format!("{}", crate::sum(&[a, b, c])), "sum(a, b, c)");
+        }
+
+        #[test]
+        fn test_sin_cos() {
+            let s1 = &Scalar::new(0.5, "s1");
+
+            let sin = Scalar::new_op(0.5f32.sin(), vec![s1], Operation::Sin, "sin");
+            let cos = Scalar::new_op(0.5f32.cos(), vec![s1], Operation::Cos, "cos");
+
+            assert_eq!(s1.sin(), sin);
+            assert_eq!(s1.cos(), cos);
+        }
+
+        #[test]
+        fn test_wide_numerics() {
+            // 2^40 is a power of two, so it is exact even as an `f32`.
+            assert_eq!((1_u64 << 40).to_f32(), 1099511627776.0);
+            assert_eq!((1_u64 << 40).to_float_exact::<f32>(), Some(1099511627776.0));
+
+            // 2^40 + 1 needs 41 bits of precision, which an `f32` rounds away but an `f64` keeps.
+            let odd = (1_u64 << 40) + 1;
+            assert_eq!(odd.to_f32(), 1099511627776.0);
+            assert_eq!(odd.to_float_exact::<f32>(), None);
+            assert_eq!(odd.to_float_exact::<f64>(), Some(1099511627777.0));
+
+            // `u128::MAX` is beyond the range of an `f32` entirely.
+            assert_eq!(u128::MAX.to_f32(), f32::INFINITY);
+            assert_eq!(u128::MAX.to_float_exact::<f32>(), None);
+            assert_eq!((-5_i128).to_float_exact::<f32>(), Some(-5.0));
+
+            assert_eq!(0.1_f64.to_float_exact::<f32>(), None);
+            assert_eq!(0.5_f64.to_float_exact::<f32>(), Some(0.5));
+            assert_eq!(Scalar64::new(0.1_f64, "").data(), 0.1);
+        }
+
+        #[test]
+        fn test_try_to_f32() {
+            // 2^24 + 1 is the smallest positive integer an `f32` cannot hold exactly.
+            assert_eq!(16_777_216_i64.try_to_f32(), Ok(16_777_216.0));
+            assert_eq!(16_777_217_i64.try_to_f32(), Err(NumericError::Inexact));
+            assert_eq!(42_u8.try_to_f32(), Ok(42.0));
+            assert_eq!((-7_i32).try_to_f32(), Ok(-7.0));
+            assert_eq!(u128::MAX.try_to_f32(), Err(NumericError::Overflow));
+        }
+
+        #[test]
+        fn test_from_str() {
+            let s1: Scalar = "2.5".parse().unwrap();
+            let s2: Scalar64 = " -1e-3\n".parse().unwrap();
+
+            assert_eq!(s1, Scalar::new(2.5, ""));
+            assert_eq!(s2.data(), -0.001);
+            assert!("abc".parse::<Scalar>().is_err());
+            assert!("".parse::<Scalar>().is_err());
+        }
+
+        #[test]
+        fn test_powi() {
+            let s1 = &Scalar::new(-1.5, "s1");
+
+            let mut result = Scalar::new_op(-3.375, vec![s1], Operation::Powi, "result");
+            result.set_params(OpParams::Powi { exponent: 3 });
+
+            assert_eq!(s1.powi(3), result);
+            assert_eq!(s1.powi(0).data(), 1.0);
+            assert_eq!(s1.powi(-2).data(), 1.0 / 2.25);
+        }
+
+        #[test]
+        fn test_ordering() {
+            let mut scalars = [3.0, 1.5, 1.50, 2.0].map(Scalar::scalar).to_vec();
+            scalars.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            let data: Vec<f32> = scalars.iter().map(Scalar::data).collect();
+            assert_eq!(data, [1.5, 1.5, 2.0, 3.0]);
+
+            scalars.push(Scalar::scalar(f32::NAN));
+            scalars.push(Scalar::scalar(f32::NEG_INFINITY));
+            assert_eq!(scalars[0].partial_cmp(&scalars[4]), None);
+
+            scalars.sort_by(Scalar::total_cmp);
+            let data: Vec<f32> = scalars.iter().map(Scalar::data).collect();
+            assert_eq!(data[..5], [f32::NEG_INFINITY, 1.5, 1.5, 2.0, 3.0]);
+            assert!(data[5].is_nan());
+        }
+
+        #[test]
+        fn test_topo_order() {
+            let a = Scalar::new(1.0, "a");
+            let b = Scalar::new(2.0, "b");
+            let c = (&a + &b).with_label("c");
+            let d = (&c * &a).with_label("d");
+
+            let labels: Vec<&str> = c.topo_order().iter().map(|s| s.label()).collect();
+            assert_eq!(labels.len(), 3);
+            assert_eq!(labels[0], "c");
+
+            let labels: Vec<&str> = d.topo_order().iter().map(|s| s.label()).collect();
+            let position = |label| labels.iter().position(|&l| l == label).unwrap();
+            assert_eq!(labels.len(), 4);
+            assert_eq!(position("d"), 0);
+            assert!(position("c") < position("a"));
+            assert!(position("c") < position("b"));
+        }
+
+        #[test]
+        fn test_operation_round_trip() {
+            let ops = [
+                Operation::Add,
+                Operation::Sub,
+                Operation::Mul,
+                Operation::Div,
+                Operation::Pow,
+                Operation::Exp,
+                Operation::Tanh,
+                Operation::ReLU,
+                Operation::Sigmoid,
+                Operation::Neg,
+                Operation::Sqrt,
+                Operation::Sum,
+                Operation::Mean,
+                Operation::Abs,
+                Operation::Max,
+                Operation::Min,
+                Operation::Sin,
+                Operation::Cos,
+                Operation::Recip,
+                Operation::Sinh,
+                Operation::Cosh,
+                Operation::Ln,
+                Operation::Base,
+            ];
+            for op in ops {
+                assert_eq!(op.to_string().parse::<Operation>(), Ok(op));
+            }
+
+            // The other powers share Pow's symbol.
+            assert_eq!(Operation::PowVar.to_string().parse(), Ok(Operation::Pow));
+            assert_eq!(Operation::Powi.to_string().parse(), Ok(Operation::Pow));
+            assert!("%".parse::<Operation>().is_err());
+        }
+
+        #[test]
+        fn test_graph_stats() {
+            let a = Scalar::new(1.0, "a");
+            let b = Scalar::new(2.0, "b");
+            let c = &a + &b;
+            let d = &c * &a;
+            let e = (&d * &d).tanh();
+
+            let stats = e.graph_stats();
+            assert_eq!(stats.nodes, 6);
+            assert_eq!(stats.edges, 7);
+            assert_eq!(stats.depth, 4);
+
+            assert_eq!(
+                a.graph_stats(),
+                GraphStats {
+                    nodes: 1,
+                    edges: 0,
+                    depth: 0
+                }
+            );
+        }
+
+        #[test]
+        fn test_integer_constants() {
+            let a = Scalar::new(1.5, "a");
+            let expected = (&a * 2.0f32).data();
+
+            // Every `Numeric` type can be used as a constant, on either side of an operator.
+            let products = [
+                &a * 2,
+                &a * 2i8,
+                &a * 2i16,
+                &a * 2i32,
+                &a * 2i64,
+                &a * 2i128,
+                &a * 2isize,
+                &a * 2u8,
+                &a * 2u16,
+                &a * 2u32,
+                &a * 2u64,
+                &a * 2u128,
+                &a * 2usize,
+                &a * 2.0f64,
+                2i8 * &a,
+                2i16 * &a,
+                2i32 * &a,
+                2i64 * &a,
+                2i128 * &a,
+                2isize * &a,
+                2u8 * &a,
+                2u16 * &a,
+                2u32 * &a,
+                2u64 * &a,
+                2u128 * &a,
+                2usize * &a,
+                2.0f32 * &a,
+            ];
+            for product in products {
+                assert_eq!(product.data(), expected);
+            }
+
+            let b = Scalar64::new(1.5, "b");
+            assert_eq!((&b * 2u8).data(), (2.0 * &b).data());
+        }
+
+        #[test]
+        fn test_grad_of() {
+            let params = [Scalar::new(2.0, "w"), Scalar::new(-1.0, "b")];
+            let x = Scalar::new(4.0, "x");
+            let y = (&(&params[0] * &x) + &params[1]).relu();
+            y.backward();
+
+            assert_eq!(y.grad_of(&params[0]), Some(4.0));
+            assert_eq!(y.grad_of(&params[1]), Some(1.0));
+            assert_eq!(y.grad_of(&y), Some(1.0));
+            assert_eq!(x.grad_of(&params[0]), None);
+        }
+
+        #[test]
+        #[cfg(feature = "half")]
+        fn test_half_numerics() {
+            use half::{bf16, f16};
+
+            // 0.1 is rounded once when made into a half-precision float (to the nearest multiple of
+            // 2^-14 for an `f16`, and of 2^-10 for a `bf16`), but not again when stored.
+            let a = Scalar::new(f16::from_f32(0.1), "a");
+            let b = Scalar64::new(bf16::from_f32(0.1), "b");
+            assert_eq!(a.data(), 1638.0 / 16384.0);
+            assert_eq!(b.data(), 0.10009765625);
+
+            let c = &a * f16::from_f32(2.0);
+            assert_eq!(c.data(), 2.0 * 1638.0 / 16384.0);
+            assert_eq!(f16::from_f32(0.1).to_float_exact::<f32>(), Some(a.data()));
+        }
+
+        #[test]
+        #[cfg(feature = "rand")]
+        fn test_random_init() {
+            use rand::rngs::StdRng;
+            use rand::SeedableRng;
+
+            let sample = |seed| {
+                let mut rng = StdRng::seed_from_u64(seed);
+                (0..8)
+                    .map(|_| Scalar::randn_with(&mut rng, 0.0, 1.0, "w").data())
+                    .collect::<Vec<f32>>()
+            };
+            assert_eq!(sample(42), sample(42));
+            assert_ne!(sample(42), sample(7));
+
+            let mut rng = StdRng::seed_from_u64(42);
+            for _ in 0..100 {
+                let u = Scalar::uniform_with(&mut rng, -0.5, 0.5, "u").data();
+                assert!((-0.5..0.5).contains(&u));
+
+                let n = Scalar64::randn_with(&mut rng, 10.0, 0.1, "n").data();
+                assert!((9.0..11.0).contains(&n));
+            }
+
+            assert!(Scalar::randn(0.0, 1.0, "w").data().is_finite());
+            assert!((2.0..3.0).contains(&Scalar::uniform(2.0, 3.0, "u").data()));
+
+            // A range so narrow that most draws round up to `hi` as an `f32`.
+            let (lo, hi) = (1.0, f32::from_bits(1f32.to_bits() + 1));
+            for _ in 0..100 {
+                assert_eq!(Scalar::uniform_with(&mut rng, lo, hi, "u").data(), lo);
+            }
+        }
+
+        #[test]
+        fn test_checked_arithmetic() {
+            let a = Scalar::new(3.0, "a");
+            let b = Scalar::new(2.0, "b");
+            let zero = Scalar::new(0.0, "zero");
+            let huge = Scalar::new(f32::MAX, "huge");
+
+            assert_eq!(a.try_add(&b).unwrap().data(), 5.0);
+            assert_eq!(a.try_sub(&b).unwrap().data(), 1.0);
+            assert_eq!(a.try_mul(&b).unwrap().data(), 6.0);
+            assert_eq!(a.try_div(&b).unwrap().data(), 1.5);
+
+            assert_eq!(a.try_div(&zero), Err(ArithError::Infinite(Operation::Div)));
+            assert_eq!(zero.try_div(&zero), Err(ArithError::NaN(Operation::Div)));
+            assert_eq!(
+                huge.try_add(&huge),
+                Err(ArithError::Infinite(Operation::Add))
+            );
+            assert_eq!(huge.try_mul(&a), Err(ArithError::Infinite(Operation::Mul)));
+            assert!(a.try_sub(&Scalar::new(f32::NAN, "nan")).is_err());
+        }
+
+        #[test]
+        fn test_values_by_label() {
+            let a = Scalar::new(1.5, "a");
+            let b = Scalar::new(2.0, "b");
+            let c = (&a * &b).with_label("c");
+            let d = (&c + &a).with_label("d");
+            // Shares a label with `c`, but is closer to the root, so it is the one kept.
+            let e = (&d - 1.0).with_label("c");
+
+            let values = e.values_by_label();
+            assert_eq!(values.len(), 4);
+            assert_eq!(values["a"], 1.5);
+            assert_eq!(values["b"], 2.0);
+            assert_eq!(values["d"], 4.5);
+            assert_eq!(values["c"], 3.5);
+            assert!(!values.contains_key(""));
+        }
+
+        #[test]
+        fn test_negative_zero() {
+            let neg = Scalar::new(-0.0, "neg");
+            let pos = Scalar::new(0.0, "pos");
+
+            assert_eq!(neg, pos);
+            assert_eq!(neg.partial_cmp(&pos), Some(std::cmp::Ordering::Equal));
+            assert_eq!(neg.total_cmp(&pos), std::cmp::Ordering::Less);
+
+            // The sign is kept, rather than normalized away.
+            assert!(neg.data().is_sign_negative());
+            assert_eq!((1.0 / &neg).data(), f32::NEG_INFINITY);
+            assert_eq!((1.0 / &pos).data(), f32::INFINITY);
+        }
+
+        #[test]
+        fn test_op_params() {
+            let a = Scalar::new(2.0, "a");
+
+            match a.pow(0.5).params() {
+                OpParams::Pow { exponent } => assert_eq!(exponent, 0.5),
+                params => panic!("Expected a `Pow` exponent, found {:?}", params),
+            }
+            match a.powi(-3).params() {
+                OpParams::Powi { exponent } => assert_eq!(exponent, -3),
+                params => panic!("Expected a `Powi` exponent, found {:?}", params),
+            }
+            assert_eq!(a.params(), OpParams::None);
+            assert_eq!(a.exp().params(), OpParams::None);
+        }
+
+        #[test]
+        fn test_params_builders() {
+            let values = [0.5, -1.0, 2.25, 0.0, 3.0];
+            let params: Vec<Scalar> = crate::nn::params_from(&values);
+            assert_eq!(params.len(), 5);
+            for (param, value) in params.iter().zip(values) {
+                assert_eq!(param.data(), value);
+                assert_eq!(param.label(), "");
+                assert!(param.requires_grad());
+            }
+
+            let labeled = crate::nn::params_labeled(&[
+                (0.1, "w0"),
+                (0.2, "w1"),
+                (0.3, "w2"),
+                (0.4, "w3"),
+                (0.5, "b"),
+            ]);
+            let labels: Vec<&str> = labeled.iter().map(|param| param.label()).collect();
+            assert_eq!(labels, ["w0", "w1", "w2", "w3", "b"]);
+            assert_eq!(labeled[4].data(), 0.5f32);
+        }
+
+        #[test]
+        fn test_debug_tree() {
+            let a = Scalar::new(1.0, "a");
+            let b = Scalar::new(2.0, "b");
+            let c = Scalar::new(3.0, "c");
+            let d = (&(&a + &b) * &c).with_label("d");
+
+            let expected = "\
+Scalar(label = d, data = 9, grad = 0, operation = *)
+  Scalar(label = , data = 3, grad = 0, operation = +)
+    Scalar(label = a, data = 1, grad = 0, operation = BASE)
+    Scalar(label = b, data = 2, grad = 0, operation = BASE)
+  Scalar(label = c, data = 3, grad = 0, operation = BASE)
+";
+            assert_eq!(d.debug_tree(), expected);
+
+            // `Debug` still only shows a single level.
+            assert!(!format!("{:?}", d).contains("label = a"));
+        }
+
+        #[test]
+        fn test_float_tolerance() {
+            // Eight representable values above 1.
+            let near = f32::from_bits(1f32.to_bits() + 8);
+
+            assert_float_eq_within(near, 1.0, Tolerance::Ulps(16));
+            assert_float_eq_within(near, 1.0, TRANSCENDENTAL);
+            assert!(std::panic::catch_unwind(|| assert_float_eq(near, 1.0)).is_err());
+
+            // Near zero, ulps are tiny, so an absolute tolerance is more useful.
+            assert!(std::panic::catch_unwind(|| {
+                assert_float_eq_within(1e-9, -1e-9, Tolerance::Ulps(16))
+            })
+            .is_err());
+            assert_float_eq_within(1e-9, -1e-9, Tolerance::Epsilon(1e-6));
+        }
+
+        #[test]
+        fn test_try_new_overflow() {
+            // Far beyond what a `u32` can hold, but well within the range of an `f32`.
+            let big = Scalar::try_new(u32::MAX as i64 * 10, "big").unwrap();
+            assert_float_eq(big.data(), 42_949_673_000.0);
+
+            let error = Scalar::try_new(u128::MAX, "huge").unwrap_err();
+            assert_eq!(error, NumericError::Overflow);
+            assert_eq!(
+                error.to_string(),
+                "number is too large to be stored as a float"
+            );
+            assert!(Scalar::try_new(f64::MAX, "huge").is_err());
+
+            // Values that are already infinite are stored as they are.
+            assert!(Scalar::try_new(f32::INFINITY, "inf")
+                .unwrap()
+                .data()
+                .is_infinite());
+            assert!(Scalar::try_new(f64::NAN, "nan").unwrap().data().is_nan());
+        }
+
+        #[test]
+        fn test_constant_compares_like_leaf() {
+            let constant = Scalar::constant(1.0);
+            let leaf = Scalar::new(1.0, "");
+
+            assert_eq!(constant, leaf);
+            assert_eq!(constant.partial_cmp(&leaf), Some(std::cmp::Ordering::Equal));
+            assert_eq!(leaf.partial_cmp(&constant), Some(std::cmp::Ordering::Equal));
+        }
+
+        #[test]
+        fn test_precision() {
+            let s1 = &Scalar::new(0.1, "s1");
+            let s2 = &Scalar::new(0.2, "s2");
+
+            assert_float_eq((s1 + s2).join_data(), 0.3);
+        }
+
+        #[test]
+        fn test_data_is_stored_bit_exact() {
+            // Data is kept as the float's own bits, with no formatting or parsing involved, so every
+            // value (including those with no short decimal form) is stored identically everywhere.
+            let values = [
+                0.1f32,
+                -0.0,
+                1.0 / 3.0,
+                f32::MIN_POSITIVE / 2.0,
+                f32::MAX,
+                -123_456.79,
+                f32::INFINITY,
+            ];
+            for value in values {
+                let a = Scalar::new(value, "a");
+                assert_eq!(a.data().to_bits(), value.to_bits());
+                assert_eq!(a.inner.data, AtomicFloat::new(value));
+                assert_eq!(a, Scalar::new(value, "b"));
+            }
+
+            let nan = Scalar::new(f32::NAN, "nan");
+            assert_eq!(nan.data().to_bits(), f32::NAN.to_bits());
+        }
+
+        #[test]
+        fn test_many_fractional_digits() {
+            #[allow(clippy::excessive_precision)]
+            let s1 = Scalar::new(1.2345678901234_f32, "s1");
+
+            assert_float_eq(s1.join_data(), 1.2345679);
+        }
+
+        #[test]
+        fn test_f64_precision() {
+            // 2^24 + 1 is the smallest positive integer that an `f32` can't represent exactly
+            let s1 = Scalar::new(16_777_217i64, "s1");
+            let s2 = Scalar64::new(16_777_217i64, "s2");
+
+            assert_eq!(s1.join_data(), 16_777_216.0);
+            assert_eq!(s2.join_data(), 16_777_217.0);
+        }
+
+        #[test]
+        fn test_partial_eq() {
+            let s1 = Scalar::new(3.2, "s1");
+            let s2 = Scalar::new(3.2, "s2");
+            let s3 = Scalar::new(3.2, "s3");
+            let s4 = Scalar::new(3.2, "s4");
+            let v = [s1.clone(), s2.clone(), s3.clone()];
+
+            assert!(v.contains(&s4));
+        }
+    }
+
+    mod backward {
+        use float_cmp::approx_eq;
+
+        use super::*;
+
+        #[test]
+        fn test_add_backward() {
+            let a = &Scalar::new(3.1, "a");
+            let b = &Scalar::new(4.2, "b");
+            let c = a + b;
+
+            c.backward();
+
+            assert_eq!(c.join_grad(), 1.0);
+            assert_eq!(b.join_grad(), 1.0);
+            assert_eq!(a.join_grad(), 1.0);
+        }
+
+        #[test]
+        fn test_sub_backward() {
+            let a = &Scalar::new(3.1, "a");
             let b = &Scalar::new(4.2, "b");
-            let mut c = a - b;
+            let c = a - b;
+
+            c.backward();
+
+            assert_float_eq(c.join_data(), -1.1);
+            assert_eq!(c.join_grad(), 1.0);
+            assert_eq!(a.join_grad(), 1.0);
+            assert_eq!(b.join_grad(), -1.0);
+        }
+
+        #[test]
+        fn test_sub_chain_backward() {
+            let a = &Scalar::new(1.5, "a");
+            let b = &Scalar::new(-2.25, "b");
+            let c = &Scalar::new(0.75, "c");
+            let d = a - b; // 3.75
+            let e = &d - c; // 3.0
+            let f = &e - a; // 1.5
+
+            f.backward();
+
+            // f(a, b, c) = (a - b) - c - a = -b - c, so a's gradient receives +1.0 from the path
+            // through `d` and -1.0 from the direct path, and must end up at exactly 0.0.
+            assert_float_eq(f.join_data(), 1.5);
+            assert_eq!(e.join_grad(), 1.0);
+            assert_eq!(d.join_grad(), 1.0);
+            assert_eq!(a.join_grad(), 0.0);
+            assert_eq!(b.join_grad(), -1.0);
+            assert_eq!(c.join_grad(), -1.0);
+        }
+
+        #[test]
+        fn test_mul_backward() {
+            let a = &Scalar::new(3.1, "a");
+            let b = &Scalar::new(4.2, "b");
+            let c = a * b;
+
+            c.backward();
+
+            assert_eq!(c.join_grad(), 1.0);
+            assert_eq!(b.join_grad(), 3.1);
+            assert_eq!(a.join_grad(), 4.2);
+        }
+
+        #[test]
+        fn test_div_backward() {
+            let a = &Scalar::new(3.1, "a");
+            let b = &Scalar::new(4.2, "b");
+            let c = a / b;
+
+            c.backward();
+
+            assert_eq!(c.join_grad(), 1.);
+            assert!(approx_eq!(f32, a.join_grad(), 1. / 4.2, ulps = 4));
+            assert_eq!(b.join_grad(), -3.1 * 4.2f32.powi(-2));
+        }
+
+        #[test]
+        fn test_pow_backward() {
+            let x = &Scalar::new(3.1, "x");
+            let y = x.pow(3.0);
+
+            y.backward();
+
+            assert_float_eq(y.join_data(), 3.1f32.powi(3));
+            assert_eq!(y.join_grad(), 1.0);
+            assert_float_eq(x.join_grad(), 3. * 3.1f32.powi(2));
+        }
+
+        #[test]
+        fn test_exp_backward() {
+            let x = &Scalar::new(1.5, "x");
+            let y = x.exp();
+
+            y.backward();
+
+            assert_eq!(y.join_grad(), 1.0);
+            assert_float_eq(x.join_grad(), 1.5f32.exp() * 1.0);
+        }
+
+        #[test]
+        fn test_tanh_backward() {
+            let x = &Scalar::new(2.0, "x");
+            let w = &Scalar::new(-3.0, "w");
+            let b = &Scalar::new(6.5, "b");
+            let xw = x * w; // -6.0
+            let s = &xw + b; // 0.5
+            let n = s.tanh();
+
+            n.backward();
+
+            // n(x, w, b) = tanh(xw + b)
+            // ∂n/∂s = 1 - tanh^2(0.5)
+            // ∂n/∂x = w * ∂n/∂s, ∂n/∂w = x * ∂n/∂s, ∂n/∂b = ∂n/∂s
+            let ds = 1. - 0.5f32.tanh().powi(2);
+            assert_float_eq(n.join_data(), 0.5f32.tanh());
+            assert_float_eq(s.join_grad(), ds);
+            assert_float_eq(b.join_grad(), ds);
+            assert_float_eq(x.join_grad(), -3. * ds);
+            assert_float_eq(w.join_grad(), 2. * ds);
+        }
+
+        #[test]
+        fn test_relu_backward() {
+            let a = &Scalar::new(3.1, "a");
+            let b = &Scalar::new(-3.1, "b");
+            let c = &Scalar::new(0.0, "c");
+            let ra = a.relu();
+            let rb = b.relu();
+            let rc = c.relu();
+
+            ra.backward();
+            rb.backward();
+            rc.backward();
+
+            assert_eq!(a.join_grad(), 1.0);
+            assert_eq!(b.join_grad(), 0.0);
+            assert_eq!(c.join_grad(), 0.0);
+        }
+
+        #[test]
+        fn test_sigmoid_backward() {
+            let a = &Scalar::new(0.0, "a");
+            let b = &Scalar::new(50.0, "b");
+            let sa = a.sigmoid();
+            let sb = b.sigmoid();
+
+            sa.backward();
+            sb.backward();
+
+            assert_eq!(a.join_grad(), 0.25);
+            assert_float_eq(sb.join_data(), 1.0);
+            assert!(b.join_grad().abs() < 1e-6);
+        }
+
+        #[test]
+        fn test_neg_backward() {
+            let a = &Scalar::new(3.1, "a");
+            let b = -a;
+
+            b.backward();
+
+            assert_eq!(b.join_data(), -3.1);
+            assert_eq!(a.join_grad(), -1.0);
+        }
+
+        #[test]
+        fn test_constant_backward() {
+            let a = &Scalar::new(3.1, "a");
+            let b = a + 2.0;
+
+            b.backward();
+
+            assert_float_eq(b.join_data(), 5.1);
+            assert_eq!(a.join_grad(), 1.0);
+
+            let c = a * 2;
+            c.backward();
+
+            assert_float_eq(c.join_data(), 6.2);
+            assert_eq!(a.join_grad(), 2.0);
+
+            let d = a - 2.0;
+            d.backward();
+
+            assert_float_eq(d.join_data(), 1.1);
+            assert_eq!(a.join_grad(), 1.0);
+
+            let e = a / 2.0;
+            e.backward();
+
+            assert_float_eq(e.join_data(), 1.55);
+            assert_eq!(a.join_grad(), 0.5);
+        }
+
+        #[test]
+        fn test_shared_leaf_backward() {
+            let a = &Scalar::new(3.0, "a");
+            let b = &Scalar::new(-2.0, "b");
+            let c = a * b; // -6.0
+            let d = &c + a; // -3.0
+
+            d.backward();
+
+            // d(a, b) = ab + a, so ∂d/∂a = b + 1 sums the contributions of both paths to `a`.
+            assert_eq!(d.join_data(), -3.0);
+            assert_eq!(a.join_grad(), -1.0);
+            assert_eq!(b.join_grad(), 3.0);
+
+            let x = &Scalar::new(3.0, "x");
+            let y = x * x;
+
+            y.backward();
+
+            assert_eq!(y.join_data(), 9.0);
+            assert_eq!(x.join_grad(), 2. * x.join_data());
+        }
+
+        #[test]
+        fn test_aliased_children_backward() {
+            let x = &Scalar::new(3.0, "x");
+
+            let sum = x + x;
+            sum.backward();
+            assert_eq!(x.join_grad(), 2.0);
+
+            let diff = x - x;
+            diff.backward();
+            assert_eq!(x.join_grad(), 0.0);
+
+            let prod = x * x;
+            prod.backward();
+            assert_eq!(x.join_grad(), 2. * x.join_data());
+
+            let quot = x / x;
+            quot.backward();
+            assert_float_eq(x.join_grad(), 0.0);
+        }
+
+        #[test]
+        fn test_nested_aliased_children_backward() {
+            let x = &Scalar::new(1.5, "x");
+            let y = x * x;
+            let z = &y * &y;
+
+            z.backward();
+
+            // z = x^4, so ∂z/∂x = 4x^3
+            assert_float_eq(z.join_data(), 1.5f32.powi(4));
+            assert_float_eq(y.join_grad(), 2. * 1.5f32.powi(2));
+            assert_float_eq(x.join_grad(), 4. * 1.5f32.powi(3));
+        }
+
+        #[test]
+        fn test_f64_backward() {
+            let a = &Scalar64::new(1.5, "a");
+            let b = &Scalar64::new(-2.25, "b");
+            let c = a * b;
+            let d = &c + a;
+
+            d.backward();
+
+            assert_eq!(d.join_data(), 1.5 * -2.25 + 1.5);
+            assert_eq!(a.join_grad(), -1.25);
+            assert_eq!(b.join_grad(), 1.5);
+        }
+
+        #[test]
+        fn test_sqrt_backward() {
+            let x = &Scalar::new(4.0, "x");
+            let y = x.sqrt();
+
+            y.backward();
+
+            assert_eq!(y.data(), 2.0);
+            assert_eq!(x.grad(), 1. / (2. * 4f32.sqrt()));
+
+            let zero = &Scalar::new(0.0, "zero");
+            let root = zero.sqrt();
+
+            root.backward();
+
+            assert_eq!(root.data(), 0.0);
+            assert_eq!(zero.grad(), f32::INFINITY);
+        }
+
+        #[test]
+        fn test_div_by_zero_backward() {
+            let a = &Scalar::new(3.0, "a");
+            let b = &Scalar::new(0.0, "b");
+            let c = a / b;
+
+            c.backward();
+
+            assert_eq!(c.data(), f32::INFINITY);
+            assert_eq!(a.grad(), f32::INFINITY);
+            assert_eq!(b.grad(), f32::NEG_INFINITY);
+
+            let zero = &Scalar::new(0.0, "zero");
+            let nan = zero / zero;
+            assert!(nan.data().is_nan());
+        }
+
+        #[test]
+        fn test_sum_backward() {
+            let items = [1.0, -2.0, 3.0, 4.5, 0.5].map(|x| Scalar::new(x, ""));
+            let refs: Vec<&Scalar> = items.iter().collect();
+            let total = crate::sum(&refs);
+
+            total.backward();
+
+            assert_eq!(total.data(), 7.0);
+            for item in items.iter() {
+                assert_eq!(item.grad(), 1.0);
+            }
+        }
+
+        #[test]
+        fn test_mean_backward() {
+            let items = [1.0, 2.0, 3.0, 6.0].map(|x| Scalar::new(x, ""));
+            let refs: Vec<&Scalar> = items.iter().collect();
+            let average = crate::mean(&refs);
+
+            average.backward();
+
+            assert_eq!(average.data(), 3.0);
+            for item in items.iter() {
+                assert_eq!(item.grad(), 0.25);
+            }
+        }
+
+        // Builds a graph out of temporaries, none of which outlive this function.
+        fn affine(x: &Scalar, w: &Scalar, b: &Scalar) -> Scalar {
+            let product = x * w;
+            &product + b
+        }
+
+        #[test]
+        fn test_returned_graph_backward() {
+            let x = Scalar::new(3.0, "x");
+            let w = Scalar::new(-2.0, "w");
+            let b = Scalar::new(0.5, "b");
+
+            let y = affine(&x, &w, &b);
+            y.backward();
+
+            assert_eq!(y.data(), -5.5);
+            assert_eq!(x.grad(), -2.0);
+            assert_eq!(w.grad(), 3.0);
+            assert_eq!(b.grad(), 1.0);
+        }
+
+        #[test]
+        fn test_loop_graph_backward() {
+            let w = Scalar::new(2.0, "w");
+
+            let mut total = Scalar::new(0.0, "total");
+            for x in 1..=4 {
+                total = &total + &(&w * x);
+            }
+            total.backward();
+
+            assert_eq!(total.data(), 20.0);
+            assert_eq!(w.grad(), 10.0);
+        }
+
+        #[test]
+        fn test_tape_loop_backward() {
+            let mut tape = crate::Tape::new();
+            let w = tape.scalar(0.5, "w");
+
+            let mut loss = tape.scalar(0.0, "loss");
+            for x in 0..10 {
+                let x = tape.scalar(x, "x");
+                loss = &loss + &(&x * &w);
+            }
+            loss.backward();
+
+            assert_eq!(loss.data(), 22.5);
+            assert_eq!(w.grad(), 45.0);
+            assert_eq!(tape.scalars().len(), 12);
+
+            tape.zero_grad();
+            assert_eq!(w.grad(), 0.0);
+        }
+
+        #[test]
+        fn test_sgd_converges() {
+            let w = Scalar::new(0.0, "w");
+            let optimizer = crate::optim::SGD::new(vec![w.clone()], 0.1);
+
+            for _ in 0..100 {
+                let loss = (&w - 3.0).pow(2.0);
+                loss.backward();
+                optimizer.step();
+                optimizer.zero_grad();
+            }
+
+            assert!((w.data() - 3.0).abs() < 1e-4);
+            assert_eq!(w.grad(), 0.0);
+        }
+
+        #[test]
+        fn test_powf_backward() {
+            let a = &Scalar::new(2.0, "a");
+            let b = &Scalar::new(3.0, "b");
+            let c = a.powf(b);
+
+            c.backward();
+
+            assert_eq!(c.data(), 8.0);
+            assert_float_eq(a.grad(), 3. * 2f32.powi(2));
+            assert_float_eq(b.grad(), 8. * 2f32.ln());
+
+            let zero = &Scalar::new(0.0, "zero");
+            let d = zero.powf(b);
+
+            d.backward();
+
+            assert_eq!(d.data(), 0.0);
+            assert_eq!(zero.grad(), 0.0);
+            // ln(0) is undefined, so the exponent receives no gradient at all.
+            assert_eq!(b.grad(), 0.0);
+        }
+
+        #[test]
+        fn test_abs_backward() {
+            let positive = &Scalar::new(2.0, "positive");
+            let negative = &Scalar::new(-2.0, "negative");
+            let zero = &Scalar::new(0.0, "zero");
+
+            for x in [positive, negative, zero] {
+                (&x.abs() * 3.0).backward();
+            }
+
+            assert_eq!(positive.grad(), 3.0);
+            assert_eq!(negative.grad(), -3.0);
+            assert_eq!(zero.grad(), 0.0);
+        }
+
+        #[test]
+        fn test_max_min_backward() {
+            let a = &Scalar::new(3.0, "a");
+            let b = &Scalar::new(-1.0, "b");
+
+            a.max(b).backward();
+            assert_eq!(a.grad(), 1.0);
+            assert_eq!(b.grad(), 0.0);
+
+            a.zero_grad();
+            a.min(b).backward();
+            assert_eq!(a.grad(), 0.0);
+            assert_eq!(b.grad(), 1.0);
+
+            // On a tie, the gradient goes to the first Scalar only.
+            let c = &Scalar::new(3.0, "c");
+            c.max(a).backward();
+            assert_eq!(c.grad(), 1.0);
+            assert_eq!(a.grad(), 0.0);
+        }
+
+        #[test]
+        fn test_detach_backward() {
+            let a = &Scalar::new(3.0, "a");
+            let b = &Scalar::new(-2.0, "b");
+            let c = &(a * 2.0).detach() * b;
+
+            c.backward();
+
+            assert_eq!(c.data(), -12.0);
+            assert_eq!(a.grad(), 0.0);
+            assert_eq!(b.grad(), 6.0);
+
+            b.zero_grad();
+            (&a.detach() * b).backward();
+            assert_eq!(a.grad(), 0.0);
+            assert_eq!(b.grad(), 3.0);
+        }
+
+        #[test]
+        fn test_second_order_backward() {
+            let x = &Scalar::new(1.5, "x");
+            let y = x.pow(3.0);
+
+            let dy_dx = y.grad_graph(&[x]).remove(0);
+            assert_float_eq(dy_dx.data(), 3. * 1.5f32.powi(2));
+            assert_eq!(x.grad(), 0.0);
+
+            dy_dx.backward();
+            assert_float_eq(x.grad(), 6. * 1.5);
+
+            // The `3` and the seed of the gradient are constants, so only `x` is a parameter.
+            let params = dy_dx.parameters();
+            assert_eq!(params.len(), 1);
+            assert!(Arc::ptr_eq(&params[0].inner, &x.inner));
+        }
+
+        #[test]
+        fn test_second_order_powvar() {
+            let w = Scalar::new(2.5, "w");
+            let x = Scalar::new(1.5, "x");
+
+            // ∂(x^w)/∂x = w·x^(w - 1), differentiated again with respect to x.
+            let (analytic, numeric) = grad_check(
+                |x: &Scalar| x.powf(&w).grad_graph(&[x]).remove(0),
+                1.5,
+                1e-2,
+            );
+            assert!((analytic - numeric).abs() < 1e-2);
+            assert_float_eq(analytic, 2.5 * 1.5 * 1.5f32.powf(0.5));
+
+            // ∂(x^w)/∂w = x^w·ln(x), differentiated again with respect to w.
+            let (analytic, numeric) =
+                grad_check(|w: &Scalar| x.powf(w).grad_graph(&[w]).remove(0), 2.5, 1e-2);
+            assert!((analytic - numeric).abs() < 1e-2);
+            assert_float_eq(analytic, 1.5f32.powf(2.5) * 1.5f32.ln().powi(2));
+        }
+
+        #[test]
+        fn test_second_order_mixed() {
+            let x = &Scalar::new(2.0, "x");
+            let w = &Scalar::new(-3.0, "w");
+            let unused = &Scalar::new(1.0, "unused");
+            let y = &(x * w) * x; // y = wx²
+
+            let grads = y.grad_graph(&[x, w, unused]);
+            assert_eq!(grads[0].data(), 2. * -3. * 2.); // ∂y/∂x = 2wx
+            assert_eq!(grads[1].data(), 4.0); // ∂y/∂w = x²
+            assert_eq!(grads[2].data(), 0.0);
+
+            // ∂²y/∂x² = 2w, and ∂²y/∂x∂w = 2x
+            grads[0].backward();
+            assert_eq!(x.grad(), -6.0);
+            assert_eq!(w.grad(), 4.0);
+        }
+
+        #[test]
+        fn test_sin_cos_backward() {
+            let zero = &Scalar::new(0.0, "zero");
+            zero.sin().backward();
+            assert_eq!(zero.grad(), 1.0);
+
+            zero.zero_grad();
+            zero.cos().backward();
+            assert_eq!(zero.grad(), 0.0);
+
+            let half_pi = &Scalar::new(std::f32::consts::FRAC_PI_2, "half_pi");
+            half_pi.sin().backward();
+            assert!(half_pi.grad().abs() < 1e-6);
+
+            half_pi.zero_grad();
+            half_pi.cos().backward();
+            assert_float_eq(half_pi.grad(), -1.0);
+        }
+
+        #[test]
+        fn test_grad_check() {
+            let (analytic, numeric) = crate::grad_check(|x: &Scalar| x * x, 1.5, 1e-2);
+            assert_eq!(analytic, 3.0);
+            assert!((analytic - numeric).abs() < 1e-3);
+
+            // Every unary operation should agree with its finite difference estimate.
+            let ops: [fn(&Scalar64) -> Scalar64; 8] = [
+                |x| x.exp(),
+                |x| x.tanh(),
+                |x| x.sigmoid(),
+                |x| x.sqrt(),
+                |x| x.sin(),
+                |x| x.cos(),
+                |x| x.pow(2.5),
+                |x| &(x * 3) / &x.exp(),
+            ];
+            for op in ops {
+                let (analytic, numeric) = crate::grad_check(op, 0.7, 1e-6);
+                assert!(
+                    (analytic - numeric).abs() < 1e-6,
+                    "{} vs {}",
+                    analytic,
+                    numeric
+                );
+            }
+        }
+
+        #[test]
+        fn test_accumulate_grad() {
+            let a = Scalar::new(1.0, "a");
+
+            a.accumulate_grad(0.5);
+            a.accumulate_grad(-2.0);
+            a.accumulate_grad(4.25);
+            assert_eq!(a.grad(), 2.75);
+
+            a.set_grad(1.0);
+            assert_eq!(a.grad(), 1.0);
+        }
+
+        #[test]
+        fn test_deep_chain_backward() {
+            let x = Scalar::new(0.5, "x");
+
+            let mut total = Scalar::new(0.0, "total");
+            for _ in 0..10_000 {
+                total = &total + &x;
+            }
+            total.backward();
+
+            assert_eq!(total.data(), 5000.0);
+            assert_eq!(x.grad(), 10_000.0);
+        }
+
+        #[test]
+        fn test_deep_chain_topology() {
+            let x = Scalar::new(2.0, "x");
+
+            let mut y = x.clone();
+            for _ in 0..5_000 {
+                y = -&y;
+            }
+            assert_eq!(y.topo_order().len(), 5_001);
+
+            y.backward();
+            assert_eq!(y.data(), 2.0);
+            assert_eq!(x.grad(), 1.0);
+        }
+
+        #[test]
+        fn test_balanced_reduce_backward() {
+            let items: Vec<Scalar> = (0..10_000).map(Scalar::scalar).collect();
+            let refs: Vec<&Scalar> = items.iter().collect();
+            let total = crate::reduce(&refs, |a, b| a + b).unwrap();
+
+            total.backward();
+
+            assert_eq!(total.data(), (0..10_000).sum::<i32>() as f32);
+            assert!(items.iter().all(|item| item.grad() == 1.0));
+
+            let factors = [1.5, -2.0, 4.0].map(Scalar::scalar);
+            let product = crate::product(&[&factors[0], &factors[1], &factors[2]]);
+            product.backward();
+
+            assert_eq!(product.data(), -12.0);
+            assert_eq!(factors[0].grad(), -8.0);
+            assert_eq!(factors[1].grad(), 6.0);
+            assert_eq!(factors[2].grad(), -3.0);
+            assert_eq!(crate::product::<f32>(&[]).data(), 1.0);
+            assert!(crate::reduce::<f32, _>(&[], |a, b| a + b).is_none());
+        }
+
+        #[test]
+        fn test_repeated_reads_large_graph() {
+            let leaves: Vec<Scalar> = (1..=1000).map(|x| Scalar::scalar(x as f32 / 8.0)).collect();
+            let squares: Vec<Scalar> = leaves.iter().map(|x| x * x).collect();
+            let total = crate::sum(&squares.iter().collect::<Vec<_>>());
+
+            // Reading the data many times always gives back exactly the value computed once.
+            let expected: f32 = squares.iter().map(|s| s.data()).sum();
+            for _ in 0..100 {
+                assert_eq!(total.data(), expected);
+            }
+
+            total.backward();
+            for (leaf, square) in leaves.iter().zip(squares.iter()) {
+                assert_eq!(square.grad(), 1.0);
+                assert_eq!(leaf.grad(), 2.0 * leaf.data());
+            }
+        }
+
+        #[test]
+        fn test_powi_backward() {
+            let x = &Scalar::new(3.0, "x");
+            x.powi(2).backward();
+            assert_eq!(x.grad(), 6.0);
+
+            x.zero_grad();
+            x.powi(0).backward();
+            assert_eq!(x.grad(), 0.0);
+
+            x.powi(-1).backward();
+            assert_eq!(x.grad(), -1.0 / 9.0);
+
+            let zero = &Scalar::new(0.0, "zero");
+            zero.powi(0).backward();
+            assert_eq!(zero.grad(), 0.0);
+        }
+
+        #[test]
+        fn test_repeated_backward() {
+            let a = &Scalar::new(3.0, "a");
+            let b = &Scalar::new(-2.0, "b");
+            let c = a * b;
+
+            // Calling `backward` again recomputes the same gradients rather than doubling them.
+            c.backward();
+            c.backward();
+            assert_eq!(a.grad(), -2.0);
+            assert_eq!(b.grad(), 3.0);
+
+            // `backward_accumulate` adds onto them instead, here for a second output sharing `a`.
+            let d = a + 10;
+            d.backward_accumulate();
+            assert_eq!(a.grad(), -1.0);
+            assert_eq!(b.grad(), 3.0);
+
+            c.zero_grad_all();
+            c.backward_accumulate();
+            c.backward_accumulate();
+            assert_eq!(a.grad(), -4.0);
+        }
+
+        #[test]
+        fn test_sgd_skips_constants() {
+            let w = Scalar::new(1.0, "w");
+            let target = Scalar::constant(3.0);
+            let optimizer = crate::optim::SGD::new(vec![w.clone(), target.clone()], 0.1);
+
+            assert!(w.requires_grad());
+            assert!(!target.requires_grad());
+            assert!((&w - &target).requires_grad());
+            assert!(!(&target * 2).requires_grad());
+
+            (&w - &target).pow(2.0).backward();
+            assert_eq!(target.grad(), 4.0);
+            optimizer.step();
+
+            assert_eq!(w.data(), 1.4);
+            assert_eq!(target.data(), 3.0);
+        }
+
+        #[test]
+        fn test_backward_prunes_constants() {
+            let constants: Vec<Scalar> = (0..100).map(Scalar::constant).collect();
+            let squares: Vec<Scalar> = constants.iter().map(|c| c.powi(2)).collect();
+            let total = crate::sum(&squares.iter().collect::<Vec<_>>()).tanh();
+            let w = &Scalar::new(0.5, "w");
+            let y = w * &total;
+
+            assert!(!total.requires_grad());
+            assert_eq!(parse_topology(&y, false).len(), 204);
+            assert_eq!(parse_topology(&y, true).len(), 2);
+
+            y.backward();
+
+            assert_eq!(w.grad(), total.data());
+            assert_eq!(total.grad(), 0.5);
+            assert!(squares.iter().all(|s| s.grad() == 0.0));
+            assert!(constants.iter().all(|c| c.grad() == 0.0));
+        }
+
+        #[test]
+        fn test_iter_sum_backward() {
+            let items = [1.5, -2.0, 4.0].map(Scalar::scalar);
+            let total: Scalar = items.iter().sum();
+
+            total.backward();
+
+            assert_eq!(total.data(), 3.5);
+            assert_eq!(total.op(), Operation::Sum);
+            assert!(items.iter().all(|item| item.grad() == 1.0));
+
+            let doubled: Scalar = items.iter().map(|item| item * 2).sum();
+            doubled.backward();
+            assert_eq!(doubled.data(), 7.0);
+            assert!(items.iter().all(|item| item.grad() == 2.0));
+        }
+
+        #[test]
+        fn test_dot_backward() {
+            let w = [0.5, -1.0, 2.0].map(Scalar::scalar);
+            let x = [3.0, 4.0, -1.5].map(Scalar::scalar);
+            let y = crate::dot(&[&w[0], &w[1], &w[2]], &[&x[0], &x[1], &x[2]]);
+
+            y.backward();
+
+            assert_eq!(y.data(), 1.5 - 4.0 - 3.0);
+            for i in 0..3 {
+                assert_eq!(w[i].grad(), x[i].data());
+                assert_eq!(x[i].grad(), w[i].data());
+            }
+        }
+
+        #[test]
+        #[should_panic(expected = "Cannot take the dot product of 2 and 1 Scalars!")]
+        fn test_dot_length_mismatch() {
+            let a = Scalar::new(1.0, "a");
+            crate::dot(&[&a, &a], &[&a]);
+        }
+
+        #[test]
+        fn test_neuron_backward() {
+            let weights = vec![
+                Scalar::new(0.5, "w0"),
+                Scalar::new(-1.0, "w1"),
+                Scalar::new(0.25, "w2"),
+            ];
+            let neuron = crate::nn::Neuron::new(weights, Scalar::new(0.1, "b"));
+            let x = [2.0, 0.5, -4.0].map(Scalar::scalar);
+
+            let y = neuron.forward(&[&x[0], &x[1], &x[2]]);
+            y.backward();
+
+            let activation: f32 = 1.0 - 0.5 - 1.0 + 0.1;
+            assert_eq!(y.data(), activation.tanh());
+
+            let params = neuron.parameters();
+            assert_eq!(params.len(), 4);
+            let slope = 1.0 - activation.tanh().powi(2);
+            for (w, x) in params.iter().zip(x.iter()) {
+                assert!(w.grad() != 0.0);
+                assert_float_eq(w.grad(), slope * x.data());
+            }
+            assert_float_eq(params[3].grad(), slope);
+        }
+
+        #[test]
+        fn test_mse_backward() {
+            let preds = [1.0, -2.0, 0.5, 3.0].map(Scalar::scalar);
+            let targets = [0.0, -1.0, 0.5, 5.0];
+            let loss = crate::nn::mse(&preds.iter().collect::<Vec<_>>(), &targets);
+
+            loss.backward();
+
+            assert_eq!(loss.data(), (1.0 + 1.0 + 0.0 + 4.0) / 4.0);
+            for (pred, target) in preds.iter().zip(targets) {
+                assert_eq!(pred.grad(), 2.0 * (pred.data() - target) / 4.0);
+            }
+        }
+
+        #[test]
+        fn test_clip_grad() {
+            let a = &Scalar::new(30.0, "a");
+            let b = &Scalar::new(-40.0, "b");
+            let c = a * b;
 
             c.backward();
+            assert_eq!(a.grad(), -40.0);
+            assert_eq!(b.grad(), 30.0);
 
-            assert_float_eq(c.join_data(), -1.1);
-            assert_eq!(c.join_grad(), 1.0);
-            assert_eq!(a.join_grad(), 1.0);
-            assert_eq!(b.join_grad(), -1.0);
+            c.clip_grad_all(5.0);
+            let norm = (a.grad().powi(2) + b.grad().powi(2)).sqrt();
+            assert!(norm <= 5.0 + 1e-5);
+            assert_float_eq(a.grad(), -4.0);
+            assert_float_eq(b.grad(), 3.0);
+
+            // Within the limit, nothing changes.
+            c.clip_grad_all(100.0);
+            assert_float_eq(a.grad(), -4.0);
+
+            a.clip_grad(1.0);
+            b.clip_grad(10.0);
+            assert_eq!(a.grad(), -1.0);
+            assert_float_eq(b.grad(), 3.0);
+
+            // The constant `1000` receives a gradient of 1, but neither counts toward the norm nor
+            // is rescaled.
+            let w = Scalar::new(1.0, "w");
+            let y = &w * 1000.0;
+            y.backward();
+            y.clip_grad_all(1000.0);
+            assert_eq!(w.grad(), 1000.0);
+            y.clip_grad_all(10.0);
+            assert_float_eq(w.grad(), 10.0);
+            assert_eq!(y.children()[1].grad(), 1.0);
         }
 
         #[test]
-        fn test_mul_backward() {
-            let a = &Scalar::new(3.1, "a");
-            let b = &Scalar::new(4.2, "b");
-            let mut c = a * b;
+        fn test_equal_leaves_are_distinct() {
+            // Two leaves holding the same data are still separate nodes, so each should receive
+            // its own gradient.
+            let a = Scalar::new(3.0, "a");
+            let a_copy = Scalar::new(3.0, "a");
+            assert_eq!(a, a_copy);
 
+            let b = &a * &a_copy;
+            b.backward();
+            assert_eq!(b.topo_order().len(), 3);
+            assert_float_eq(a.grad(), 3.0);
+            assert_float_eq(a_copy.grad(), 3.0);
+
+            let c = &a + &a_copy;
             c.backward();
+            assert_float_eq(a.grad(), 1.0);
+            assert_float_eq(a_copy.grad(), 1.0);
+        }
 
-            assert_eq!(c.join_grad(), 1.0);
-            assert_eq!(b.join_grad(), 3.1);
-            assert_eq!(a.join_grad(), 4.2);
+        #[test]
+        fn test_constant_lhs_derive() {
+            let a = Scalar::new(4.0, "a");
+
+            let b = 2.0 - &a;
+            b.backward();
+            assert_float_eq(b.data(), -2.0);
+            assert_float_eq(a.grad(), -1.0);
+
+            let c = 2i32 / &a;
+            c.backward();
+            assert_float_eq(c.data(), 0.5);
+            assert_float_eq(a.grad(), -0.125);
+
+            let d = 3u8 * &a;
+            d.backward();
+            assert_float_eq(d.data(), 12.0);
+            assert_float_eq(a.grad(), 3.0);
+
+            let e = 1.5 + &a;
+            e.backward();
+            assert_float_eq(e.data(), 5.5);
+            assert_float_eq(a.grad(), 1.0);
         }
 
         #[test]
-        fn test_div_backward() {
-            let a = &Scalar::new(3.1, "a");
-            let b = &Scalar::new(4.2, "b");
-            let mut c = a / b;
+        fn test_recip_derive() {
+            let a = Scalar::new(2.0, "a");
+            let b = a.recip();
+            b.backward();
+            assert_float_eq(b.data(), 0.5);
+            assert_float_eq(a.grad(), -0.25);
 
+            let zero = Scalar::new(0.0, "zero");
+            let c = zero.recip();
             c.backward();
+            assert_eq!(c.data(), f32::INFINITY);
+            assert_eq!(zero.grad(), f32::NEG_INFINITY);
+        }
 
-            assert_eq!(c.join_grad(), 1.);
-            assert!(approx_eq!(f32, a.join_grad(), 1. / 4.2, ulps = 4));
-            assert_eq!(b.join_grad(), -3.1 * 4.2f32.powi(-2));
+        #[test]
+        fn test_batch_backward() {
+            let a = Scalar::new(3.0, "a");
+            let b = Scalar::new(2.0, "b");
+
+            let loss = &a * &b;
+            let regularizer = a.powi(2);
+            batch_backward(&[(&loss, 1.0), (&regularizer, 0.5)]);
+
+            // ∂/∂a of (ab + 0.5a^2) = b + a, and ∂/∂b = a.
+            assert_float_eq(a.grad(), 5.0);
+            assert_float_eq(b.grad(), 3.0);
+
+            // One output computed from the other still receives the gradient from both paths.
+            let total = &loss + &regularizer;
+            batch_backward(&[(&total, 2.0), (&loss, 1.0)]);
+            assert_float_eq(loss.grad(), 3.0);
+            assert_float_eq(a.grad(), 3.0 * 2.0 + 2.0 * 2.0 * 3.0);
+            assert_float_eq(b.grad(), 3.0 * 3.0);
+        }
+
+        #[test]
+        fn test_hyperbolic_derive() {
+            let x = Scalar::new(0.0, "x");
+            let y = x.sinh();
+            y.backward();
+            assert_float_eq(y.data(), 0.0);
+            assert_float_eq(x.grad(), 1.0);
+
+            let y = x.cosh();
+            y.backward();
+            assert_float_eq(y.data(), 1.0);
+            assert_float_eq(x.grad(), 0.0);
+
+            let x = Scalar::new(0.5, "x");
+            let y = x.sinh();
+            y.backward();
+            assert_float_eq(x.grad(), 0.5f32.cosh());
+
+            let y = x.cosh();
+            y.backward();
+            assert_float_eq(x.grad(), 0.5f32.sinh());
+        }
+
+        #[test]
+        fn test_softmax_derive() {
+            let logits = [
+                Scalar::new(1.0, "a"),
+                Scalar::new(2.0, "b"),
+                Scalar::new(3.0, "c"),
+            ];
+            let refs: Vec<&Scalar> = logits.iter().collect();
+            let probs = crate::nn::softmax(&refs);
+            let p: Vec<f32> = probs.iter().map(|prob| prob.data()).collect();
+
+            assert_float_eq(p.iter().sum(), 1.0);
+            assert!(p[0] < p[1] && p[1] < p[2]);
+
+            // ∂pᵢ/∂logitⱼ = pᵢ(δᵢⱼ - pⱼ), so each row of the Jacobian comes from one backward pass.
+            for (i, prob) in probs.iter().enumerate() {
+                prob.backward();
+                for (j, logit) in logits.iter().enumerate() {
+                    let kronecker = if i == j { 1.0 } else { 0.0 };
+                    assert_float_eq_within(logit.grad(), p[i] * (kronecker - p[j]), TRANSCENDENTAL);
+                }
+            }
+
+            // Logits large enough to overflow `exp` on their own still give finite probabilities.
+            let large = [Scalar::new(1000.0, "a"), Scalar::new(1000.0, "b")];
+            let probs = crate::nn::softmax(&[&large[0], &large[1]]);
+            assert_float_eq(probs[0].data(), 0.5);
+        }
+
+        #[test]
+        fn test_ln_derive() {
+            let a = Scalar::new(4.0, "a");
+            let b = a.ln();
+            b.backward();
+            assert_float_eq(b.data(), 4f32.ln());
+            assert_float_eq(a.grad(), 0.25);
+        }
+
+        #[test]
+        fn test_cross_entropy_derive() {
+            let logits = [
+                Scalar::new(1.0, "a"),
+                Scalar::new(2.0, "b"),
+                Scalar::new(3.0, "c"),
+            ];
+            let refs: Vec<&Scalar> = logits.iter().collect();
+            let p: Vec<f32> = crate::nn::softmax(&refs)
+                .iter()
+                .map(|prob| prob.data())
+                .collect();
+
+            let loss = crate::nn::cross_entropy(&refs, 0);
+            loss.backward();
+            assert_float_eq_within(loss.data(), -p[0].ln(), TRANSCENDENTAL);
+
+            // The gradient of each logit is its probability, minus one for the target class.
+            assert!(logits[0].grad() < 0.0);
+            assert!(logits[1].grad() > 0.0 && logits[2].grad() > 0.0);
+            assert_float_eq_within(logits[0].grad(), p[0] - 1.0, TRANSCENDENTAL);
+            assert_float_eq_within(logits[1].grad(), p[1], TRANSCENDENTAL);
+            assert_float_eq_within(logits[2].grad(), p[2], TRANSCENDENTAL);
+        }
+
+        #[test]
+        fn test_parallel_graphs() {
+            fn assert_send_sync<T: Send + Sync>() {}
+            assert_send_sync::<Scalar>();
+            assert_send_sync::<Scalar64>();
+
+            // Independent subgraphs built on separate threads, then summed on this one.
+            let x = Scalar::new(2.0, "x");
+            let partials: Vec<Scalar> = std::thread::scope(|scope| {
+                let workers: Vec<_> = (0..4)
+                    .map(|t| {
+                        let x = &x;
+                        scope.spawn(move || {
+                            let items: Vec<Scalar> = (0..250).map(|i| x * (t * 250 + i)).collect();
+                            sum(&items.iter().collect::<Vec<_>>())
+                        })
+                    })
+                    .collect();
+                workers.into_iter().map(|w| w.join().unwrap()).collect()
+            });
+
+            let total = sum(&partials.iter().collect::<Vec<_>>());
+            total.backward();
+            assert_eq!(total.data(), 2.0 * 499_500.0);
+            assert_eq!(x.grad(), 499_500.0);
+
+            // Backward passes on separate threads, all adding onto the gradient of the same leaf.
+            x.zero_grad();
+            std::thread::scope(|scope| {
+                for _ in 0..4 {
+                    scope.spawn(|| {
+                        let items: Vec<Scalar> = (0..1_000).map(|_| &x * 1).collect();
+                        sum(&items.iter().collect::<Vec<_>>()).backward_accumulate();
+                    });
+                }
+            });
+            assert_eq!(x.grad(), 4_000.0);
+        }
+
+        #[test]
+        fn test_fold_derive() {
+            let items = [
+                Scalar::new(2.0, "a"),
+                Scalar::new(3.0, "b"),
+                Scalar::new(4.0, "c"),
+                Scalar::new(5.0, "d"),
+            ];
+            let refs: Vec<&Scalar> = items.iter().collect();
+
+            let product = fold(&refs, Operation::Mul).unwrap();
+            product.backward();
+            assert_float_eq(product.data(), 120.0);
+            for item in items.iter() {
+                // The gradient of each item is the product of all of the others.
+                assert_float_eq(item.grad(), 120.0 / item.data());
+            }
+
+            // The order of the items is kept, so non-associative operations work too.
+            assert_float_eq(fold(&refs, Operation::Sub).unwrap().data(), -10.0);
+            assert_float_eq(fold(&refs[..1], Operation::Div).unwrap().data(), 2.0);
+
+            assert_eq!(
+                fold(&refs, Operation::Exp).unwrap_err(),
+                FoldError::NotBinary(Operation::Exp)
+            );
+            assert_eq!(
+                fold::<f32>(&[], Operation::Add).unwrap_err(),
+                FoldError::Empty
+            );
+        }
+
+        #[test]
+        fn test_product_derive() {
+            let factors = [2.0, 3.0, 4.0].map(Scalar::scalar);
+            let product = crate::product(&factors.iter().collect::<Vec<_>>());
+            product.backward();
+
+            assert_eq!(product.data(), 24.0);
+            assert_eq!(factors.map(|factor| factor.grad()), [12.0, 8.0, 6.0]);
+
+            // A zero factor makes every other gradient zero, but not its own.
+            let factors = [2.0, 0.0, 4.0].map(Scalar::scalar);
+            let product = crate::product(&factors.iter().collect::<Vec<_>>());
+            product.backward();
+
+            assert_eq!(product.data(), 0.0);
+            assert_eq!(factors.map(|factor| factor.grad()), [0.0, 8.0, 0.0]);
+
+            // With two zero factors, every gradient is zero.
+            let factors = [0.0, 3.0, 0.0].map(Scalar::scalar);
+            let product = crate::product(&factors.iter().collect::<Vec<_>>());
+            product.backward();
+            assert_eq!(factors.map(|factor| factor.grad()), [0.0, 0.0, 0.0]);
+        }
+
+        #[test]
+        fn test_backward_into() {
+            let w = Scalar::new(0.5, "w");
+            let examples = [(2.0, 1.0), (-1.0, 3.0)];
+            let losses: Vec<Scalar> = examples
+                .iter()
+                .map(|&(x, y)| (&(&w * x) - y).powi(2))
+                .collect();
+
+            // ∂/∂w of (wx - y)² is 2x(wx - y).
+            let expected: Vec<f32> = examples
+                .iter()
+                .map(|&(x, y)| 2.0 * x * (0.5 * x - y))
+                .collect();
+
+            // The first example starts the batch afresh, and the rest accumulate onto it.
+            w.set_grad(100.0);
+            for (i, loss) in losses.iter().enumerate() {
+                loss.backward_into(i > 0);
+            }
+            assert_float_eq(w.grad(), expected[0] + expected[1]);
+
+            // Accumulating on every example also keeps whatever the gradient held before.
+            for loss in losses.iter() {
+                loss.backward_into(true);
+            }
+            assert_float_eq(w.grad(), 2.0 * (expected[0] + expected[1]));
+
+            losses[1].backward_into(false);
+            assert_float_eq(w.grad(), expected[1]);
+        }
+
+        #[test]
+        fn test_backward_with_seed() {
+            let a = Scalar::new(1.5, "a");
+            let b = Scalar::new(-2.0, "b");
+            let c = (&(&a * &b) + &a.exp()).tanh();
+
+            c.backward();
+            let unit = [a.grad(), b.grad(), c.grad()];
+
+            c.backward_with(2.0);
+            assert_float_eq(a.grad(), 2.0 * unit[0]);
+            assert_float_eq(b.grad(), 2.0 * unit[1]);
+            assert_float_eq(c.grad(), 2.0);
+
+            // Like `backward`, the gradients of an earlier pass are cleared first.
+            c.backward_with(1.0);
+            assert_eq!([a.grad(), b.grad(), c.grad()], unit);
+        }
+
+        #[test]
+        fn test_tape_replay() {
+            let mut tape = Tape::new();
+            let x = tape.scalar(3.0, "x");
+            let b = tape.scalar(1.0, "b");
+            let y = &(&x * &x) + &b;
+            tape.record(&y);
+
+            tape.set_input(0, 2.0);
+            assert_float_eq(tape.forward(), 5.0);
+            tape.backward();
+            assert_float_eq(x.grad(), 4.0);
+            assert_float_eq(b.grad(), 1.0);
+
+            tape.set_input(0, -1.5);
+            tape.set_input(1, 0.25);
+            assert_float_eq(tape.forward(), 2.5);
+            assert_float_eq(y.data(), 2.5);
+            tape.backward();
+            assert_float_eq(x.grad(), -3.0);
+            assert_float_eq(b.grad(), 1.0);
+        }
+
+        #[test]
+        fn test_tape_replay_matches_rebuild() {
+            let build = |x: &Scalar, w: &Scalar| {
+                let h = (&(x * w) - 0.5).sigmoid();
+                let items = [h.clone(), x.powi(3), w.pow(1.5), x.max(w), h.ln().abs()];
+                &crate::mean(&items.iter().collect::<Vec<_>>()) * &x.cos().recip()
+            };
+
+            let mut tape = Tape::new();
+            let x = tape.scalar(0.3, "x");
+            let w = tape.scalar(1.2, "w");
+            tape.record(&build(&x, &w));
+
+            for (xv, wv) in [(0.7, 0.4), (-0.2, 2.5), (1.1, 1.1)] {
+                tape.set_input(0, xv);
+                tape.set_input(1, wv);
+                let replayed = tape.forward();
+                tape.backward();
+                let grads = (x.grad(), w.grad());
+
+                let (x2, w2) = (Scalar::new(xv, "x"), Scalar::new(wv, "w"));
+                let rebuilt = build(&x2, &w2);
+                rebuilt.backward();
+                assert_eq!(replayed, rebuilt.data());
+                assert_eq!(grads, (x2.grad(), w2.grad()));
+            }
+        }
+
+        #[test]
+        fn test_constant_survives_into_derive() {
+            let a = Scalar::new(2.0, "a");
+            let b = a.pow(3.0);
+            assert!(format!("{:?}", b).ends_with("operation = ^, exponent = 3)"));
+            assert!(!format!("{:?}", a).contains("exponent"));
+
+            // The constant is kept on the node itself, so every handle sharing it (even one made
+            // long after the node was) derives with it.
+            let shared = b.clone();
+            let c = &shared * 1.0;
+            c.backward();
+            assert_float_eq(a.grad(), 12.0);
+
+            shared.set_grad(1.0);
+            a.zero_grad();
+            shared.derive();
+            assert_float_eq(a.grad(), 12.0);
+        }
+
+        #[test]
+        fn test_assert_close() {
+            let a = Scalar::new(0.1, "a");
+            let b = (&a * &a).with_label("b");
+            b.backward();
+
+            assert_grad_close(&a, 0.2, 4);
+            assert_data_close(&b, 0.01, 4);
+
+            let far = std::panic::catch_unwind(|| assert_grad_close(&a, 0.2001, 4));
+            assert!(far.is_err());
+            let far = std::panic::catch_unwind(|| assert_data_close(&b, 0.0101, 4));
+            assert!(far.is_err());
+        }
+
+        #[test]
+        fn test_set_data_and_recompute() {
+            let x = Scalar::new(3.0, "x");
+            let b = Scalar::new(0.5, "b");
+            let h = &x * &x;
+            let y = (&h + &b).sqrt();
+            y.backward();
+            assert_float_eq(y.data(), 9.5f32.sqrt());
+
+            x.set_data(-2.0);
+            b.set_data(5);
+            assert_float_eq(h.data(), 9.0);
+            y.recompute();
+            assert_float_eq(h.data(), 4.0);
+            assert_float_eq(y.data(), 3.0);
+
+            // The gradients then follow the new data.
+            y.backward();
+            assert_float_eq(x.grad(), -2.0 / 3.0);
+            assert_float_eq(b.grad(), 1.0 / 6.0);
+        }
+
+        #[test]
+        fn test_dual_matches_backward() {
+            let x = Scalar::new(3.0, "x");
+            let y = &x * &x;
+            y.backward();
+
+            let dual = Dual::variable(3.0);
+            assert_eq!((dual * dual).value(), y.data());
+            assert_eq!((dual * dual).derivative(), x.grad());
+
+            // A function using every operation, differentiated both ways.
+            for at in [0.3, 1.7, 2.5] {
+                let x = Scalar::new(at, "x");
+                let y =
+                    &(&(&x.tanh() * &x.exp()) / &(&x + 2.0)) - &(&x.ln().sin() + &x.sqrt().cos());
+                let y = &(&y * &x.powi(3)) + &(-&x.pow(1.5));
+                y.backward();
+
+                let d = Dual::variable(at);
+                let two = Dual::constant(2.0);
+                let z = (d.tanh() * d.exp()) / (d + two) - (d.ln().sin() + d.sqrt().cos());
+                let z = z * d.powi(3) + -d.pow(1.5);
+
+                assert_float_eq(z.value(), y.data());
+                assert_float_eq_within(z.derivative(), x.grad(), TRANSCENDENTAL);
+            }
+
+            assert_eq!(Dual::constant(5.0).derivative(), 0.0);
+            assert_eq!(Dual64::new(1.5, 2).to_string(), "1.5 + 2ε");
+        }
+
+        #[test]
+        fn test_parameters() {
+            let w = Scalar::new(2.0, "w");
+            let b = Scalar::new(1.0, "b");
+            let y = &(&(&w * &w) * 3.0) + &(&b - &Scalar::constant(0.5));
+
+            let params = y.parameters();
+            assert_eq!(params.len(), 2);
+            assert!(params.iter().any(|p| Arc::ptr_eq(&p.inner, &w.inner)));
+            assert!(params.iter().any(|p| Arc::ptr_eq(&p.inner, &b.inner)));
+
+            assert!(Scalar::constant(1.0).parameters().is_empty());
+            assert_eq!(w.parameters().len(), 1);
+        }
+
+        #[test]
+        fn test_owned_operands() {
+            let a = Scalar::new(2.0, "a");
+            let b = Scalar::new(3.0, "b");
+
+            // Every operand here is moved in, apart from the first uses of `a` and `b`.
+            let c = a.clone() * b.clone();
+            let d = -(c + &a) / 2 - b.clone().exp() * Scalar::constant(0.5);
+            d.backward();
+
+            assert_float_eq(d.data(), -4.0 - 0.5 * 3f32.exp());
+            assert_float_eq(a.grad(), -(3.0 + 1.0) / 2.0);
+            assert_float_eq(b.grad(), -2.0 / 2.0 - 0.5 * 3f32.exp());
+            assert_eq!(d.children().len(), 2);
+        }
+
+        #[test]
+        fn test_grad_snapshot() {
+            let w = Scalar::new(2.0, "w");
+            let b = Scalar::new(-1.0, "b");
+            let y = (&(&w * &w) + &b).tanh();
+            y.backward();
+
+            let snapshot = y.grad_snapshot();
+            assert_eq!(snapshot.len(), 2);
+            for (label, grad) in &snapshot {
+                match *label {
+                    "w" => assert_float_eq(*grad, w.grad()),
+                    "b" => assert_float_eq(*grad, b.grad()),
+                    label => panic!("Unexpected leaf `{}`", label),
+                }
+            }
+            assert_float_eq(
+                snapshot.iter().find(|(l, _)| *l == "b").unwrap().1,
+                1.0 - 3f32.tanh().powi(2),
+            );
+
+            // Later passes do not change an earlier snapshot.
+            y.zero_grad_all();
+            assert!(snapshot.iter().all(|(_, grad)| *grad != 0.0));
+        }
+
+        #[test]
+        fn test_jacobian() {
+            let x = Scalar::new(2.0, "x");
+            let y = Scalar::new(5.0, "y");
+            let sum = &x + &y;
+            let product = &x * &y;
+
+            let rows = jacobian(&[&sum, &product], &[&x, &y]);
+            assert_eq!(rows, vec![vec![1.0, 1.0], vec![5.0, 2.0]]);
+
+            // The gradient of `y` from the first row must not leak into the second.
+            let rows = jacobian(&[&sum, &x.exp()], &[&x, &y]);
+            assert_eq!(rows[1][1], 0.0);
+            assert!(jacobian(&[], &[&x]).is_empty());
+        }
+
+        #[test]
+        fn test_as_learnable_leaf() {
+            let w = Scalar::new(2.0, "w");
+            let b = Scalar::new(1.0, "b");
+            let hidden = &w * &b;
+            let y = &hidden + &Scalar::constant(1.0);
+
+            let collected = [&w, &hidden, &b, &y];
+            let params: Vec<&Scalar> = collected
+                .iter()
+                .filter_map(|node| node.as_learnable_leaf())
+                .collect();
+            assert_eq!(params.len(), 2);
+            assert!(Arc::ptr_eq(&params[0].inner, &w.inner));
+            assert!(Arc::ptr_eq(&params[1].inner, &b.inner));
+        }
+
+        #[test]
+        fn test_backward_through() {
+            let a = Scalar::new(2.0, "a");
+            let b = Scalar::new(3.0, "b");
+            let left = &a * &b;
+            let right = &a.powi(2) * &b;
+            let y = &left + &right;
+
+            y.backward();
+            assert_float_eq(a.grad(), 3.0 + 2.0 * 2.0 * 3.0);
+
+            // Excluding `right` removes its branch's contribution to `a` and `b`.
+            y.backward_through(&[&left, &a, &b]);
+            assert_float_eq(a.grad(), 3.0);
+            assert_float_eq(b.grad(), 2.0);
+            assert_eq!(right.grad(), 0.0);
+            assert_eq!(right.children()[0].grad(), 0.0);
+
+            // A leaf outside the subset gets nothing, even through an included parent.
+            y.backward_through(&[&left, &a]);
+            assert_float_eq(a.grad(), 3.0);
+            assert_eq!(b.grad(), 0.0);
+
+            // A constant child of an allowed Scalar is outside the subset too.
+            let offset = Scalar::constant(4.0);
+            let z = &(&a * &offset) + &b;
+            z.backward_through(&[&z.children()[0], &a]);
+            assert_float_eq(a.grad(), 4.0);
+            assert_eq!(offset.grad(), 0.0);
+            z.backward_through(&[&z.children()[0], &offset]);
+            assert_float_eq(offset.grad(), 2.0);
+
+            // With only the root, nothing below it receives a gradient.
+            y.backward_through(&[]);
+            assert_eq!(y.grad(), 1.0);
+            assert_eq!(left.grad(), 0.0);
+        }
+
+        #[test]
+        fn test_accumulate_clears_constant_intermediates() {
+            let a = Scalar::new(2.0, "a");
+            let constants = &Scalar::constant(1.0) + &Scalar::constant(3.0);
+            let y = &a * &constants;
+
+            y.backward_accumulate();
+            y.backward_accumulate();
+
+            // The leaf sums both passes, while the constant sum only holds the latest one.
+            assert_eq!(a.grad(), 8.0);
+            assert_eq!(constants.grad(), 2.0);
+        }
+
+        #[test]
+        fn test_unary_derive() {
+            let x = &Scalar::new(0.5, "x");
+            let y = Scalar::new_full(0.5f32.tanh(), vec![x], 1.0, Operation::Tanh, "y");
+
+            y.derive();
+
+            assert_float_eq(x.grad(), 1. - 0.5f32.tanh().powi(2));
+        }
+
+        #[test]
+        fn test_diamond_backward() {
+            let a = &Scalar::new(2.0, "a");
+            let b = &Scalar::new(3.0, "b");
+            let c = &Scalar::new(4.0, "c");
+            let ab = a * b;
+            let ac = a * c;
+            let e = &ab + &ac;
+
+            e.backward();
+
+            // e(a, b, c) = ab + ac, so ∂e/∂a = b + c, ∂e/∂b = a, ∂e/∂c = a
+            assert_eq!(e.data(), 14.0);
+            assert_eq!(a.grad(), 7.0);
+            assert_eq!(b.grad(), 2.0);
+            assert_eq!(c.grad(), 2.0);
+        }
+
+        #[test]
+        fn test_uneven_depth_backward() {
+            let a = &Scalar::new(2.0, "a");
+            let b = &Scalar::new(3.0, "b");
+            let c = &Scalar::new(-1.0, "c");
+            let d = &Scalar::new(4.0, "d");
+            let x = a * b; // 6.0
+            let w = &x * c; // -6.0
+            let y = &w + d; // -2.0
+            let z = &y * &x; // -12.0
+
+            z.backward();
+
+            // `x` is used both directly by `z` and (two levels further down) by `w`, so it must
+            // only be derived once the gradient from `w` has arrived:
+            // ∂z/∂x = y + ∂z/∂y * ∂y/∂w * ∂w/∂x = -2 + 6 * 1 * -1 = -8
+            assert_eq!(z.data(), -12.0);
+            assert_eq!(y.grad(), 6.0);
+            assert_eq!(w.grad(), 6.0);
+            assert_eq!(x.grad(), -8.0);
+            assert_eq!(a.grad(), -24.0);
+            assert_eq!(b.grad(), -16.0);
+            assert_eq!(c.grad(), 36.0);
+            assert_eq!(d.grad(), 6.0);
+        }
+
+        #[test]
+        fn test_zero_grad_all() {
+            let a = &Scalar::new(3.0, "a");
+            let b = &Scalar::new(-2.0, "b");
+            let c = a * b;
+            let d = &c + a;
+
+            d.backward();
+            let (grad_a, grad_b, grad_c) = (a.grad(), b.grad(), c.grad());
+
+            d.zero_grad_all();
+            assert_eq!(a.grad(), 0.0);
+            assert_eq!(b.grad(), 0.0);
+            assert_eq!(c.grad(), 0.0);
+            assert_eq!(d.grad(), 0.0);
+
+            d.backward();
+            assert_eq!(a.grad(), grad_a);
+            assert_eq!(b.grad(), grad_b);
+            assert_eq!(c.grad(), grad_c);
+        }
+
+        // A regular backward pass, run in its own process by `test_backward_is_silent` so that
+        // anything it writes to stderr can be inspected.
+        #[test]
+        fn backward_quietly() {
+            let a = &Scalar::new(3.0, "a");
+            let b = &Scalar::new(-2.0, "b");
+            let c = a * b;
+            let d = &c + a;
+
+            d.backward();
+        }
+
+        #[test]
+        fn test_backward_is_silent() {
+            let output = std::process::Command::new(std::env::current_exe().unwrap())
+                .args([
+                    "tests::backward::backward_quietly",
+                    "--exact",
+                    "--nocapture",
+                    "--test-threads=1",
+                ])
+                .output()
+                .unwrap();
+
+            assert!(output.status.success());
+            assert!(
+                output.stderr.is_empty(),
+                "{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
         }
 
         #[test]
         fn test_compound_fn() {
             let a = &Scalar::new(-4.0, "a");
             let b = &Scalar::new(2.0, "b");
-            let mut c = a + b; // -2.0
-            c._label = "c";
-            let mut d = a * b; // -8.0
-            d._label = "d";
-            let mut e = &d / &c; // 4.0
-            e._label = "e";
+            let c = (a + b).with_label("c"); // -2.0
+            let d = (a * b).with_label("d"); // -8.0
+            let e = (&d / &c).with_label("e"); // 4.0
             let f = &Scalar::new(10.0, "f");
-            let mut g = f / &e;
-            g._label = "g";
+            let g = (f / &e).with_label("g");
 
             g.backward();
 
@@ -567,11 +4225,116 @@ mod tests {
             assert_float_eq(a.join_grad(), -0.625);
         }
     }
-}
 
-/// Given two floats `a` and `b`, asserts that the two floats are equal to each other, taking
-/// into account precision and rounding errors that might change some of the later decimal
-/// points of either float.
-fn assert_float_eq(a: f32, b: f32) {
-    assert!(approx_eq!(f32, a, b, ulps = 4));
+    #[cfg(feature = "serde")]
+    mod serialize {
+        use super::*;
+
+        #[test]
+        fn test_round_trip() {
+            let a = &Scalar::new(3.0, "a");
+            let b = &Scalar::new(-1.5, "b");
+            let c = (a + b).with_label("c");
+            c.backward();
+
+            let json = serde_json::to_string(&c).unwrap();
+            let restored: Scalar = serde_json::from_str(&json).unwrap();
+
+            assert_eq!(restored, c);
+            assert_eq!(format!("{:?}", restored), format!("{:?}", c));
+            assert_eq!(restored.inner._children[1].grad(), 1.0);
+
+            let d = a * 2;
+            let restored: Scalar =
+                serde_json::from_str(&serde_json::to_string(&d).unwrap()).unwrap();
+            assert!(restored.children()[0].requires_grad());
+            assert!(!restored.children()[1].requires_grad());
+        }
+
+        #[test]
+        fn test_round_trip_shares_nodes() {
+            let a = &Scalar::new(2.0, "a");
+            let b = &(a * a).pow(3.0);
+
+            let restored: Scalar =
+                serde_json::from_str(&serde_json::to_string(b).unwrap()).unwrap();
+
+            assert_eq!(&restored, b);
+            let square = &restored.inner._children[0];
+            assert!(Arc::ptr_eq(
+                &square.inner._children[0].inner,
+                &square.inner._children[1].inner
+            ));
+
+            restored.backward();
+            assert_eq!(
+                square.inner._children[0].grad(),
+                3. * 4f32.powi(2) * 2. * 2.
+            );
+        }
+
+        #[test]
+        fn test_round_trip_deep_chain() {
+            let x = Scalar::new(2.0, "x");
+
+            let mut y = x.clone();
+            for _ in 0..5_000 {
+                y = -&y;
+            }
+
+            let restored: Scalar =
+                serde_json::from_str(&serde_json::to_string(&y).unwrap()).unwrap();
+            assert_eq!(restored.topo_order().len(), 5_001);
+            assert_eq!(restored.data(), 2.0);
+        }
+
+        #[test]
+        fn test_invalid_child_index() {
+            let json = r#"[{"data":1.0,"grad":0.0,"operation":"Neg","label":"","children":[0]}]"#;
+
+            assert!(serde_json::from_str::<Scalar>(json).is_err());
+        }
+
+        #[test]
+        fn test_wrong_child_count() {
+            let json = r#"[
+                {"data":1.0,"grad":0.0,"operation":"Base","label":"a","children":[]},
+                {"data":1.0,"grad":0.0,"operation":"Add","label":"","children":[0]}
+            ]"#;
+
+            let error = serde_json::from_str::<Scalar>(json).unwrap_err();
+            assert!(error.to_string().contains("needs 2"));
+        }
+
+        #[test]
+        fn test_missing_exponent() {
+            for op in ["Pow", "Powi"] {
+                let json = format!(
+                    r#"[
+                        {{"data":2.0,"grad":0.0,"operation":"Base","label":"a","children":[]}},
+                        {{"data":4.0,"grad":0.0,"operation":"{}","label":"","children":[0]}}
+                    ]"#,
+                    op
+                );
+
+                let error = serde_json::from_str::<Scalar>(&json).unwrap_err();
+                assert!(error.to_string().contains("needs an exponent"));
+            }
+        }
+
+        #[test]
+        fn test_fractional_powi_exponent() {
+            let json = r#"[
+                {"data":2.0,"grad":0.0,"operation":"Base","label":"a","children":[]},
+                {"data":4.0,"grad":0.0,"operation":"Powi","label":"","exponent":2.5,"children":[0]}
+            ]"#;
+
+            let error = serde_json::from_str::<Scalar>(json).unwrap_err();
+            assert!(error.to_string().contains("not an integer exponent"));
+
+            let integral = json.replace("2.5", "2.0");
+            let restored: Scalar = serde_json::from_str(&integral).unwrap();
+            assert_eq!(restored.params(), OpParams::Powi { exponent: 2 });
+        }
+    }
 }
@@ -0,0 +1,138 @@
+//! Building blocks for neural networks, made out of Scalars.
+
+use crate::{dot, mean, sum, Float, GenericScalar};
+
+/// A single neuron, computing `tanh(w · x + b)` for its weights `w`, bias `b` and inputs `x`. Most
+/// code should use the [`Neuron`] alias.
+///
+/// ```
+/// use minigrad::nn::Neuron;
+/// use minigrad::{Derivable, Scalar};
+///
+/// let neuron = Neuron::new(vec![Scalar::new(0.5, "w0"), Scalar::new(-0.25, "w1")], Scalar::new(0.1, "b"));
+/// let x = [Scalar::scalar(1.0), Scalar::scalar(2.0)];
+///
+/// let y = neuron.forward(&[&x[0], &x[1]]);
+/// y.backward();
+/// assert_eq!(y.data(), 0.1f32.tanh());
+/// ```
+#[derive(Debug, Clone)]
+pub struct GenericNeuron<F: Float> {
+    weights: Vec<GenericScalar<F>>,
+    bias: GenericScalar<F>,
+}
+
+/// A neuron with `f32` parameters.
+pub type Neuron = GenericNeuron<f32>;
+
+/// A neuron with `f64` parameters.
+pub type Neuron64 = GenericNeuron<f64>;
+
+impl<F: Float> GenericNeuron<F> {
+    /// Creates a neuron with one weight per input, plus a bias.
+    pub fn new(weights: Vec<GenericScalar<F>>, bias: GenericScalar<F>) -> Self {
+        GenericNeuron { weights, bias }
+    }
+
+    /// Computes `tanh(w · x + b)` for the inputs `x`. Panics if there is not exactly one input per
+    /// weight.
+    pub fn forward(&self, inputs: &[&GenericScalar<F>]) -> GenericScalar<F> {
+        let weights: Vec<&GenericScalar<F>> = self.weights.iter().collect();
+        (&dot(&weights, inputs) + &self.bias).tanh()
+    }
+
+    /// Returns the weights and then the bias of this neuron, sharing their nodes (for instance, to
+    /// hand them to an optimizer).
+    pub fn parameters(&self) -> Vec<GenericScalar<F>> {
+        let mut params = self.weights.clone();
+        params.push(self.bias.clone());
+
+        params
+    }
+}
+
+/// Creates an unlabelled leaf Scalar for each of `values`, in order, such as the initial weights of
+/// a layer (which can then be handed to `GenericNeuron::new` or an optimizer).
+///
+/// ```
+/// use minigrad::nn::params_from;
+/// use minigrad::Scalar;
+///
+/// let weights: Vec<Scalar> = params_from(&[0.5, -0.25, 1.0]);
+/// assert_eq!(weights[1].data(), -0.25);
+/// ```
+pub fn params_from<F: Float>(values: &[F]) -> Vec<GenericScalar<F>> {
+    values
+        .iter()
+        .map(|&value| GenericScalar::scalar(value))
+        .collect()
+}
+
+/// Like [`params_from`], but labels each Scalar with the label paired with its value.
+pub fn params_labeled<F: Float>(values: &[(F, &'static str)]) -> Vec<GenericScalar<F>> {
+    values
+        .iter()
+        .map(|&(value, label)| GenericScalar::new(value, label))
+        .collect()
+}
+
+/// Computes the mean squared error between `preds` and `targets`, the mean of (predᵢ - targetᵢ)².
+/// The targets are constants, so gradients only flow into the predictions: each predᵢ receives
+/// 2(predᵢ - targetᵢ)/n. Panics if there is not exactly one target per prediction.
+pub fn mse<F: Float>(preds: &[&GenericScalar<F>], targets: &[F]) -> GenericScalar<F> {
+    assert_eq!(
+        preds.len(),
+        targets.len(),
+        "Cannot compare {} predictions against {} targets!",
+        preds.len(),
+        targets.len()
+    );
+
+    let errors: Vec<GenericScalar<F>> = preds
+        .iter()
+        .zip(targets.iter())
+        .map(|(&pred, &target)| (pred - target).powi(2))
+        .collect();
+
+    mean(&errors.iter().collect::<Vec<_>>())
+}
+
+/// Turns `logits` into probabilities that sum to 1, each proportional to e^logitᵢ. The largest
+/// logit is subtracted from every logit first (which leaves the probabilities unchanged), so that
+/// large logits cannot overflow `exp`. Every probability depends on every logit, through the
+/// shared sum, so gradients flow from each output into all of the logits.
+pub fn softmax<F: Float>(logits: &[&GenericScalar<F>]) -> Vec<GenericScalar<F>> {
+    let max = logits
+        .iter()
+        .map(|logit| logit.data())
+        .fold(F::neg_infinity(), F::max);
+
+    let exps: Vec<GenericScalar<F>> = logits.iter().map(|&logit| (logit - max).exp()).collect();
+    let total = sum(&exps.iter().collect::<Vec<_>>());
+
+    exps.iter().map(|exp| exp / &total).collect()
+}
+
+/// Computes the cross-entropy loss of `logits` against the class `target`, -ln(softmax(logits)ₜ).
+/// Rather than taking the logarithm of a probability (which could underflow to 0), this is computed
+/// as ln(Σ e^(logitᵢ - m)) - (logitₜ - m), where m is the largest logit. The gradient of each logit
+/// is then its probability, minus one for the target. Panics if `target` is not the index of one of
+/// the logits.
+pub fn cross_entropy<F: Float>(logits: &[&GenericScalar<F>], target: usize) -> GenericScalar<F> {
+    assert!(
+        target < logits.len(),
+        "Cannot take class {} as the target of {} logits!",
+        target,
+        logits.len()
+    );
+
+    let max = logits
+        .iter()
+        .map(|logit| logit.data())
+        .fold(F::neg_infinity(), F::max);
+
+    let exps: Vec<GenericScalar<F>> = logits.iter().map(|&logit| (logit - max).exp()).collect();
+    let log_total = sum(&exps.iter().collect::<Vec<_>>()).ln();
+
+    &log_total - &(logits[target] - max)
+}
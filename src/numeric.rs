@@ -1,16 +1,130 @@
-//!
 #![warn(missing_debug_implementations, missing_docs)]
 #![allow(dead_code)]
 
-/// A trait that applies to the following numeric types: unsigned and signed integers (i8 -> i32,
-/// u8 -> u32, isize and usize) and floating point numbers (f32). 64-bit types are not yet
-/// supported (u64, i64, f64).
-pub trait Numeric {
+use std::cmp::Ordering;
+use std::fmt::{Debug, Display};
+
+use num_traits::{NumCast, ToPrimitive};
+
+/// The floating point types that a Scalar can store its data and gradient as: `f32` and `f64`.
+pub trait Float: num_traits::Float + Numeric + Display + Debug {
+    /// Converts any primitive number (most often a literal constant, such as the `2` in a
+    /// derivative rule) into this float type.
+    fn cast(n: impl ToPrimitive) -> Self {
+        <Self as NumCast>::from(n).expect("Every primitive number should be castable to a float!")
+    }
+
+    /// Checks whether this float and `other` are at most `ulps` representable values apart.
+    fn ulps_eq(self, other: Self, ulps: u32) -> bool;
+
+    /// Orders this float and `other` by IEEE 754's total order, in which -NaN < -∞ < ... < -0 <
+    /// +0 < ... < +∞ < NaN.
+    fn total_cmp(&self, other: &Self) -> Ordering;
+
+    /// Returns the bit pattern of this float, widened to 64 bits, so that it can be kept in an
+    /// atomic integer.
+    fn to_bits64(self) -> u64;
+
+    /// Recovers a float from the bits returned by `to_bits64`.
+    fn from_bits64(bits: u64) -> Self;
+}
+
+impl Float for f32 {
+    fn ulps_eq(self, other: Self, ulps: u32) -> bool {
+        float_cmp::approx_eq!(f32, self, other, ulps = ulps as i32)
+    }
+
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        f32::total_cmp(self, other)
+    }
+
+    fn to_bits64(self) -> u64 {
+        self.to_bits() as u64
+    }
+
+    fn from_bits64(bits: u64) -> Self {
+        f32::from_bits(bits as u32)
+    }
+}
+
+impl Float for f64 {
+    fn ulps_eq(self, other: Self, ulps: u32) -> bool {
+        float_cmp::approx_eq!(f64, self, other, ulps = ulps as i64)
+    }
+
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        f64::total_cmp(self, other)
+    }
+
+    fn to_bits64(self) -> u64 {
+        self.to_bits()
+    }
+
+    fn from_bits64(bits: u64) -> Self {
+        f64::from_bits(bits)
+    }
+}
+
+/// A trait that applies to the following numeric types: unsigned and signed integers (i8 -> i128,
+/// u8 -> u128, isize and usize) and floating point numbers (f32 and f64, as well as `half::f16`
+/// and `half::bf16` with the `half` feature).
+///
+/// Not every such number can be stored exactly as a float: an `f32` only holds integers exactly up
+/// to 2^24 (and an `f64` up to 2^53), and rounds anything larger (or more precise) to the nearest
+/// value it can represent. Numbers beyond the float's range, such as `u128::MAX` as an `f32`,
+/// become infinite. `to_float_exact` can be used to check for either case up front.
+pub trait Numeric: ToPrimitive + NumCast + Copy + PartialEq {
     /// Every time that implements `Numeric` must be castable to a f32, so that it can be used to
     /// store a Scalar's `data` and `grad` values.
     fn to_f32(self) -> f32;
+
+    /// Casts this number to the float type `F` that a Scalar stores its `data` and `grad` values
+    /// as.
+    fn to_float<F: Float>(self) -> F {
+        F::cast(self)
+    }
+
+    /// Casts this number to the float type `F` like `to_float`, but only if `F` can represent it
+    /// exactly, returning `None` if it would be rounded or would overflow to infinity.
+    fn to_float_exact<F: Float>(self) -> Option<F> {
+        let float: F = self.to_float();
+        match <Self as NumCast>::from(float) {
+            Some(round_trip) if round_trip == self => Some(float),
+            _ => None,
+        }
+    }
+
+    /// Casts this number to an `f32` like `to_f32`, but returns an error instead of rounding it or
+    /// overflowing to infinity.
+    fn try_to_f32(self) -> Result<f32, NumericError> {
+        match self.to_float_exact::<f32>() {
+            Some(float) => Ok(float),
+            None if self.to_f32().is_infinite() => Err(NumericError::Overflow),
+            None => Err(NumericError::Inexact),
+        }
+    }
+}
+
+/// The error returned when a number cannot be stored exactly as a float.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumericError {
+    /// The number is beyond the range of the float, and would become infinite.
+    Overflow,
+    /// The number is within the range of the float, but would be rounded.
+    Inexact,
 }
 
+impl Display for NumericError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NumericError::Overflow => write!(f, "number is too large to be stored as a float"),
+            NumericError::Inexact => write!(f, "number cannot be stored exactly as a float"),
+        }
+    }
+}
+
+impl std::error::Error for NumericError {}
+
 // For eacch of the following implementations, the `to_f32` method is simply a cast from the value
 // specified to an `f32`.
 impl Numeric for i8 {
@@ -37,6 +151,12 @@ impl Numeric for i64 {
     }
 }
 
+impl Numeric for i128 {
+    fn to_f32(self) -> f32 {
+        self as f32
+    }
+}
+
 impl Numeric for isize {
     fn to_f32(self) -> f32 {
         self as f32
@@ -67,6 +187,12 @@ impl Numeric for u64 {
     }
 }
 
+impl Numeric for u128 {
+    fn to_f32(self) -> f32 {
+        self as f32
+    }
+}
+
 impl Numeric for usize {
     fn to_f32(self) -> f32 {
         self as f32
@@ -78,3 +204,26 @@ impl Numeric for f32 {
         self
     }
 }
+
+impl Numeric for f64 {
+    fn to_f32(self) -> f32 {
+        self as f32
+    }
+}
+
+// Half-precision floats are only stored by a Scalar after being widened to its own float type.
+// Since both `f32` and `f64` can represent every `f16` and `bf16` value exactly, this widening
+// never rounds: the only rounding happens when the value is first made into an `f16` or `bf16`.
+#[cfg(feature = "half")]
+impl Numeric for half::f16 {
+    fn to_f32(self) -> f32 {
+        half::f16::to_f32(self)
+    }
+}
+
+#[cfg(feature = "half")]
+impl Numeric for half::bf16 {
+    fn to_f32(self) -> f32 {
+        half::bf16::to_f32(self)
+    }
+}
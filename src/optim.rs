@@ -0,0 +1,46 @@
+//! Optimizers, which update parameters using the gradients computed by `backward`.
+
+use crate::{Float, GenericScalar};
+
+/// Stochastic gradient descent: every step moves each parameter against its gradient, by `lr`
+/// times the gradient. Parameters are shared with the caller (cloning a Scalar shares its node), so
+/// updates made by `step` are visible through the caller's own handles. Most code should use the
+/// [`SGD`] alias.
+///
+/// Since a Scalar computed from a parameter does not change when the parameter does, the graph
+/// (for instance, the loss) should be rebuilt after every step.
+#[derive(Debug)]
+pub struct GenericSGD<F: Float> {
+    lr: F,
+    params: Vec<GenericScalar<F>>,
+}
+
+/// Stochastic gradient descent over `f32` Scalars.
+pub type SGD = GenericSGD<f32>;
+
+/// Stochastic gradient descent over `f64` Scalars.
+pub type SGD64 = GenericSGD<f64>;
+
+impl<F: Float> GenericSGD<F> {
+    /// Creates an optimizer over `params`, with learning rate `lr`.
+    pub fn new(params: Vec<GenericScalar<F>>, lr: F) -> Self {
+        GenericSGD { lr, params }
+    }
+
+    /// Nudges every parameter by `-lr * grad`, skipping any that do not require gradients (such as
+    /// constants).
+    pub fn step(&self) {
+        for param in self.params.iter().filter(|param| param.requires_grad()) {
+            param.update_data(param.join_data() - self.lr * param.join_grad());
+        }
+    }
+
+    /// Resets the gradient of every parameter to zero. `backward` clears the gradients of the graph
+    /// it is called on by itself, but gradients accumulate across calls to `backward_accumulate`,
+    /// so this should be called after every step when using it.
+    pub fn zero_grad(&self) {
+        for param in self.params.iter() {
+            param.zero_grad();
+        }
+    }
+}
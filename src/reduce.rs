@@ -0,0 +1,190 @@
+//! Reductions that combine any number of Scalars into a single Scalar.
+
+use std::fmt::Display;
+use std::iter::Sum;
+
+use crate::{Float, GenericScalar, Operation};
+
+/// Adds every Scalar in `items` together. Rather than chaining `+` (which builds one node per
+/// addition, and so a graph as deep as `items` is long), the sum is a single node with every item
+/// as a child. Since ∂(a + b + ...)/∂a = 1, every item receives the sum's gradient unchanged
+/// during `backward`. The sum of an empty slice is a Scalar holding zero.
+///
+/// ```
+/// use minigrad::{sum, Derivable, Scalar};
+///
+/// let a = Scalar::new(1.0, "a");
+/// let b = Scalar::new(2.0, "b");
+/// let total = sum(&[&a, &b]);
+///
+/// total.backward();
+/// assert_eq!(total.data(), 3.0);
+/// assert_eq!(a.grad(), 1.0);
+/// ```
+pub fn sum<F: Float>(items: &[&GenericScalar<F>]) -> GenericScalar<F> {
+    let data = items
+        .iter()
+        .fold(F::zero(), |total, item| total + item.join_data());
+
+    GenericScalar::new_op(data, items.to_vec(), Operation::Sum, "")
+}
+
+/// Averages every Scalar in `items`. Like [`sum`], this is a single node with every item as a
+/// child, and since ∂((a + b + ...) / n)/∂a = 1/n, every item receives the mean's gradient scaled
+/// by `1/n` during `backward`. The mean of an empty slice is a Scalar holding zero (rather than
+/// the NaN that `0 / 0` would give), which has no children and so propagates nothing.
+pub fn mean<F: Float>(items: &[&GenericScalar<F>]) -> GenericScalar<F> {
+    let data = if items.is_empty() {
+        F::zero()
+    } else {
+        let total = items
+            .iter()
+            .fold(F::zero(), |total, item| total + item.join_data());
+        total / F::cast(items.len())
+    };
+
+    GenericScalar::new_op(data, items.to_vec(), Operation::Mean, "")
+}
+
+/// Combines every Scalar in `items` with the binary operation `op`, pairing them up into a balanced
+/// binary tree rather than folding them from the left. The resulting graph is only about log₂(n)
+/// operations deep rather than n, which keeps graph traversals shallow. `op` should be
+/// associative (like `+` or `*`), since the items are not combined in left-to-right order. Returns
+/// `None` if `items` is empty.
+///
+/// ```
+/// use minigrad::{reduce, Scalar};
+///
+/// let items = [1.0, 2.0, 3.0, 4.0, 5.0].map(Scalar::scalar);
+/// let total = reduce(&items.iter().collect::<Vec<_>>(), |a, b| a + b).unwrap();
+/// assert_eq!(total.data(), 15.0);
+/// ```
+pub fn reduce<F, G>(items: &[&GenericScalar<F>], op: G) -> Option<GenericScalar<F>>
+where
+    F: Float,
+    G: Fn(&GenericScalar<F>, &GenericScalar<F>) -> GenericScalar<F>,
+{
+    let mut level: Vec<GenericScalar<F>> = items.iter().map(|&item| item.clone()).collect();
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [a, b] => op(a, b),
+                [a] => a.clone(),
+                _ => unreachable!(),
+            })
+            .collect();
+    }
+
+    level.pop()
+}
+
+/// The error returned by [`fold`] when it cannot combine its items.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FoldError {
+    /// The operation does not combine two Scalars into one (as `Operation::Exp` or
+    /// `Operation::Base` do not).
+    NotBinary(Operation),
+    /// There were no items to combine.
+    Empty,
+}
+
+impl Display for FoldError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FoldError::NotBinary(op) => write!(f, "`{}` is not a binary operation", op),
+            FoldError::Empty => write!(f, "cannot fold an empty list of Scalars"),
+        }
+    }
+}
+
+impl std::error::Error for FoldError {}
+
+/// Combines every Scalar in `items` with the binary operation `op`, from left to right, so that
+/// folding `[a, b, c]` under `Operation::Sub` gives `(a - b) - c`. Unlike [`reduce`], the order is
+/// kept, so `op` need not be associative; the binary operations are `Add`, `Sub`, `Mul`, `Div`,
+/// `PowVar`, `Max` and `Min`. Returns an error for any other operation, or if `items` is empty.
+///
+/// ```
+/// use minigrad::{fold, Operation, Scalar};
+///
+/// let items = [2.0, 3.0, 4.0].map(Scalar::scalar);
+/// let product = fold(&items.iter().collect::<Vec<_>>(), Operation::Mul).unwrap();
+/// assert_eq!(product.data(), 24.0);
+/// ```
+pub fn fold<F: Float>(
+    items: &[&GenericScalar<F>],
+    op: Operation,
+) -> Result<GenericScalar<F>, FoldError> {
+    type Combine<F> = fn(&GenericScalar<F>, &GenericScalar<F>) -> GenericScalar<F>;
+    let combine: Combine<F> = match op {
+        Operation::Add => |a, b| a + b,
+        Operation::Sub => |a, b| a - b,
+        Operation::Mul => |a, b| a * b,
+        Operation::Div => |a, b| a / b,
+        Operation::PowVar => |a, b| a.powf(b),
+        Operation::Max => |a, b| a.max(b),
+        Operation::Min => |a, b| a.min(b),
+        op => return Err(FoldError::NotBinary(op)),
+    };
+
+    let (first, rest) = items.split_first().ok_or(FoldError::Empty)?;
+    Ok(rest
+        .iter()
+        .fold((*first).clone(), |total, &item| combine(&total, item)))
+}
+
+/// Multiplies every Scalar in `items` together, as a balanced tree of `*` operations (see
+/// [`reduce`]). The product of an empty slice is a Scalar holding one.
+///
+/// Each factor's gradient is the product of all of the others. Since this comes from
+/// backpropagating through the `*` nodes, rather than from dividing the whole product by the
+/// factor, it is still exact when a factor is zero.
+pub fn product<F: Float>(items: &[&GenericScalar<F>]) -> GenericScalar<F> {
+    reduce(items, |a, b| a * b)
+        .unwrap_or_else(|| GenericScalar::new_op(F::one(), vec![], Operation::Base, ""))
+}
+
+/// Adds up an iterator of Scalars with [`sum`], so that `iter.sum::<Scalar>()` builds a single
+/// `Sum` node. The node shares (rather than copies) each of the Scalars it is given, so they all
+/// receive gradients from it.
+///
+/// ```
+/// use minigrad::{Derivable, Scalar};
+///
+/// let items = [1.0, 2.0, 3.0].map(Scalar::scalar);
+/// let total: Scalar = items.iter().sum();
+///
+/// total.backward();
+/// assert_eq!(total.data(), 6.0);
+/// assert_eq!(items[0].grad(), 1.0);
+/// ```
+impl<'a, F: Float> Sum<&'a GenericScalar<F>> for GenericScalar<F> {
+    fn sum<I: Iterator<Item = &'a GenericScalar<F>>>(iter: I) -> Self {
+        sum(&iter.collect::<Vec<_>>())
+    }
+}
+
+/// Like the implementation for `&Scalar`, but for an iterator of owned Scalars (such as the results
+/// of a `map`), which the resulting `Sum` node keeps alive as its children.
+impl<F: Float> Sum for GenericScalar<F> {
+    fn sum<I: Iterator<Item = GenericScalar<F>>>(iter: I) -> Self {
+        let items: Vec<GenericScalar<F>> = iter.collect();
+        sum(&items.iter().collect::<Vec<_>>())
+    }
+}
+
+/// Computes the dot product of `a` and `b`, Σ aᵢbᵢ, as a `Sum` node over the elementwise products.
+/// During `backward`, each aᵢ receives bᵢ times the dot product's gradient, and each bᵢ receives
+/// aᵢ times it. Panics if `a` and `b` have different lengths.
+pub fn dot<F: Float>(a: &[&GenericScalar<F>], b: &[&GenericScalar<F>]) -> GenericScalar<F> {
+    assert_eq!(
+        a.len(),
+        b.len(),
+        "Cannot take the dot product of {} and {} Scalars!",
+        a.len(),
+        b.len()
+    );
+
+    a.iter().zip(b.iter()).map(|(&x, &y)| x * y).sum()
+}
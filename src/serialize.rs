@@ -0,0 +1,183 @@
+//! Serialization of Scalar graphs, enabled by the `serde` feature.
+//!
+//! A Scalar is serialized as its whole graph, flattened into a list of nodes in which every node
+//! comes after all of its children (so the Scalar itself is the last node). Each node refers to its
+//! children by their index in that list, which lets a node shared by several parents be serialized
+//! once and rebuilt as a single shared node.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{Float, GenericScalar, OpParams, Operation};
+
+#[derive(Serialize, Deserialize)]
+struct SerializedNode<F> {
+    data: F,
+    grad: F,
+    operation: Operation,
+    label: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    exponent: Option<F>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    constant: bool,
+    children: Vec<usize>,
+}
+
+// Flattens the graph rooted at `node` into a list of nodes, children first. The order comes from
+// the (iterative) topological sort, reversed, so that deep graphs cannot overflow the call stack.
+// Nodes are identified by address, so that a node reached through several parents is only added
+// once.
+fn flatten<F: Float>(node: &GenericScalar<F>) -> Vec<SerializedNode<F>> {
+    let mut order = node.topo_order();
+    order.reverse();
+
+    let indices: HashMap<*const (), usize> = order
+        .iter()
+        .enumerate()
+        .map(|(index, node)| (Arc::as_ptr(&node.inner) as *const (), index))
+        .collect();
+
+    order
+        .into_iter()
+        .map(|node| SerializedNode {
+            data: node.join_data(),
+            grad: node.join_grad(),
+            operation: node.inner._op,
+            label: node.inner._label.get().to_string(),
+            exponent: match node.inner._params {
+                OpParams::None => None,
+                OpParams::Pow { exponent } => Some(exponent),
+                OpParams::Powi { exponent } => Some(F::cast(exponent)),
+            },
+            constant: node.children().is_empty() && !node.requires_grad(),
+            children: node
+                .inner
+                ._children
+                .iter()
+                .map(|child| indices[&(Arc::as_ptr(&child.inner) as *const ())])
+                .collect(),
+        })
+        .collect()
+}
+
+// The number of children a node with operation `op` must have, or `None` for reductions, which
+// take any number. There is deliberately no wildcard arm, so that a new operation cannot be added
+// without deciding how many children it takes.
+fn expected_children(op: Operation) -> Option<usize> {
+    match op {
+        Operation::Base => Some(0),
+        Operation::Pow
+        | Operation::Powi
+        | Operation::Exp
+        | Operation::Tanh
+        | Operation::ReLU
+        | Operation::Sigmoid
+        | Operation::Neg
+        | Operation::Sqrt
+        | Operation::Abs
+        | Operation::Sin
+        | Operation::Cos
+        | Operation::Recip
+        | Operation::Sinh
+        | Operation::Cosh
+        | Operation::Ln => Some(1),
+        Operation::Add
+        | Operation::Sub
+        | Operation::Mul
+        | Operation::Div
+        | Operation::PowVar
+        | Operation::Max
+        | Operation::Min => Some(2),
+        Operation::Sum | Operation::Mean => None,
+    }
+}
+
+impl<F: Float + Serialize> Serialize for GenericScalar<F> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        flatten(self).serialize(serializer)
+    }
+}
+
+impl<'de, F: Float + Deserialize<'de>> Deserialize<'de> for GenericScalar<F> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let nodes = Vec::<SerializedNode<F>>::deserialize(deserializer)?;
+
+        let mut built: Vec<GenericScalar<F>> = Vec::with_capacity(nodes.len());
+        for node in nodes {
+            let children = node
+                .children
+                .iter()
+                .map(|&index| {
+                    built.get(index).ok_or_else(|| {
+                        D::Error::custom(format!(
+                            "node {} refers to child {}, which does not come before it",
+                            built.len(),
+                            index
+                        ))
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            if let Some(expected) = expected_children(node.operation) {
+                if children.len() != expected {
+                    return Err(D::Error::custom(format!(
+                        "node {} is a `{:?}` node with {} children, but needs {}",
+                        built.len(),
+                        node.operation,
+                        children.len(),
+                        expected
+                    )));
+                }
+            }
+
+            // Labels are `&'static str`s, so a deserialized label has to be leaked to live that
+            // long. Empty labels (the most common kind) are not allocated at all.
+            let label: &'static str = if node.label.is_empty() {
+                ""
+            } else {
+                Box::leak(node.label.into_boxed_str())
+            };
+
+            let mut scalar =
+                GenericScalar::new_full(node.data, children, node.grad, node.operation, label);
+            let params = match (node.operation, node.exponent) {
+                (Operation::Pow, Some(exponent)) => OpParams::Pow { exponent },
+                (Operation::Powi, Some(exponent)) => OpParams::Powi {
+                    // `to_i32` would truncate a fractional exponent, so those are rejected first.
+                    exponent: Some(exponent)
+                        .filter(|exponent| exponent.fract() == F::zero())
+                        .and_then(|exponent| exponent.to_i32())
+                        .ok_or_else(|| {
+                            D::Error::custom(format!("`{}` is not an integer exponent", exponent))
+                        })?,
+                },
+                (op @ (Operation::Pow | Operation::Powi), None) => {
+                    return Err(D::Error::custom(format!(
+                        "a `{:?}` node needs an exponent",
+                        op
+                    )))
+                }
+                (op, Some(_)) => {
+                    return Err(D::Error::custom(format!(
+                        "a `{:?}` node cannot have an exponent",
+                        op
+                    )))
+                }
+                (_, None) => OpParams::None,
+            };
+            scalar.set_params(params);
+            if node.constant {
+                Arc::get_mut(&mut scalar.inner)
+                    .expect("A newly created Scalar should not be shared yet!")
+                    ._requires_grad = false;
+            }
+            built.push(scalar);
+        }
+
+        built
+            .pop()
+            .ok_or_else(|| D::Error::custom("a Scalar graph needs at least one node"))
+    }
+}
@@ -0,0 +1,122 @@
+//! A tape that keeps track of the Scalars created for a computation.
+
+use crate::{parse_topology, Derivable, Float, GenericScalar, Numeric};
+
+/// Records every leaf Scalar created through it, so that a computation's inputs and parameters can
+/// be managed together (for instance, to reset all of their gradients between backward passes).
+/// Since Scalars own their children, expressions built from the tape's Scalars (even inside a
+/// loop) backpropagate like any others. Most code should use the [`Tape`] alias.
+///
+/// A computation can also be recorded with `record`, and then replayed with new inputs (set with
+/// `set_input`) by `forward` and `backward`, without building its graph again.
+///
+/// ```
+/// use minigrad::{Derivable, Tape};
+///
+/// let mut tape = Tape::new();
+/// let w = tape.scalar(0.5, "w");
+///
+/// let mut loss = tape.scalar(0.0, "loss");
+/// for x in 1..=3 {
+///     loss = &loss + &(&tape.scalar(x, "x") * &w);
+/// }
+/// loss.backward();
+///
+/// assert_eq!(w.grad(), 6.0);
+/// ```
+#[derive(Debug, Default)]
+pub struct GenericTape<F: Float> {
+    scalars: Vec<GenericScalar<F>>,
+    // Every Scalar computed by the recorded computation, in an order in which each comes after all
+    // of its children, so that replaying them in order recomputes the output.
+    sequence: Vec<GenericScalar<F>>,
+    output: Option<GenericScalar<F>>,
+}
+
+/// A tape of `f32` Scalars.
+pub type Tape = GenericTape<f32>;
+
+/// A tape of `f64` Scalars.
+pub type Tape64 = GenericTape<f64>;
+
+impl<F: Float> GenericTape<F> {
+    /// Creates an empty tape.
+    pub fn new() -> Self {
+        GenericTape {
+            scalars: vec![],
+            sequence: vec![],
+            output: None,
+        }
+    }
+
+    /// Creates a leaf Scalar holding `data` and records it on this tape. The returned Scalar
+    /// shares its node with the tape's copy, so gradients computed through one are visible
+    /// through the other.
+    pub fn scalar(&mut self, data: impl Numeric, label: &'static str) -> GenericScalar<F> {
+        let scalar = GenericScalar::new(data, label);
+        self.scalars.push(scalar.clone());
+
+        scalar
+    }
+
+    /// Returns every Scalar recorded on this tape, in the order they were created.
+    pub fn scalars(&self) -> &[GenericScalar<F>] {
+        &self.scalars
+    }
+
+    /// Resets the gradient of every Scalar recorded on this tape to zero.
+    pub fn zero_grad(&self) {
+        for scalar in self.scalars.iter() {
+            scalar.zero_grad();
+        }
+    }
+
+    /// Records the computation of `output`, so that it can be replayed by `forward` and `backward`
+    /// once the inputs have changed. Replaces any computation recorded before.
+    pub fn record(&mut self, output: &GenericScalar<F>) {
+        self.sequence = parse_topology(output, false)
+            .into_iter()
+            .rev()
+            .filter(|node| !node.children().is_empty())
+            .cloned()
+            .collect();
+        self.output = Some(output.clone());
+    }
+
+    /// Replaces the data of the `index`th Scalar created on this tape (counting from 0) with
+    /// `value`. The recorded computation does not see the new value until `forward` is called.
+    /// Panics if fewer than `index + 1` Scalars were created on this tape.
+    pub fn set_input(&self, index: usize, value: impl Numeric) {
+        let input = self.scalars.get(index).unwrap_or_else(|| {
+            panic!(
+                "Cannot set input {} of a tape with {} Scalars!",
+                index,
+                self.scalars.len()
+            )
+        });
+        input.update_data(value.to_float());
+    }
+
+    /// Recomputes every Scalar in the recorded computation from the current inputs, in order, and
+    /// returns the data of its output. Panics if nothing has been recorded.
+    pub fn forward(&self) -> F {
+        for node in self.sequence.iter() {
+            node.update_data(node.evaluate());
+        }
+
+        self.recorded().data()
+    }
+
+    /// Computes the gradients of the recorded computation's output, like calling `backward` on
+    /// it. Should be called after `forward`, so that the gradients match the current inputs.
+    /// Panics if nothing has been recorded.
+    pub fn backward(&self) {
+        self.recorded().backward();
+    }
+
+    fn recorded(&self) -> &GenericScalar<F> {
+        self.output
+            .as_ref()
+            .expect("A computation should be recorded before it is replayed!")
+    }
+}
@@ -0,0 +1,41 @@
+use minigrad::{Operation, Scalar};
+
+// Collects the labels of every leaf reachable from `node`, the way an external tool walking the
+// graph would.
+fn leaf_labels(node: &Scalar, labels: &mut Vec<&'static str>) {
+    if node.op() == Operation::Base {
+        labels.push(node.label());
+    }
+    for child in node.children() {
+        leaf_labels(child, labels);
+    }
+}
+
+#[test]
+fn test_walk_children() {
+    let a = Scalar::new(2.0, "a");
+    let b = Scalar::new(3.0, "b");
+    let c = &a * &b;
+
+    assert_eq!(c.op(), Operation::Mul);
+    assert_eq!(c.children().len(), 2);
+    assert_eq!(c.children()[0], a);
+    assert_eq!(c.children()[1], b);
+
+    let mut labels = vec![];
+    leaf_labels(&c, &mut labels);
+    assert_eq!(labels, ["a", "b"]);
+}
+
+#[test]
+fn test_walk_constants() {
+    let a = Scalar::new(2.0, "a");
+    let d = (&a * 4).tanh();
+
+    assert_eq!(d.op(), Operation::Tanh);
+    let product = &d.children()[0];
+    assert_eq!(product.op(), Operation::Mul);
+    assert_eq!(product.children()[1].data(), 4.0);
+    assert_eq!(product.children()[1].label(), "");
+    assert!(a.children().is_empty());
+}